@@ -0,0 +1,108 @@
+//! Encoding benchmarks, also checking the number of allocations per encoding
+
+#![feature(test)]
+
+extern crate test;
+extern crate pwasm_abi;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use test::Bencher;
+use pwasm_abi::eth::{AbiEncode, Sink};
+use pwasm_abi::types::U256;
+
+struct CountingAllocator;
+
+thread_local! {
+	// per thread, since the benchmarks may run in parallel as tests
+	static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.with(|count| count.set(count.get() + 1));
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOCATIONS.with(|count| count.set(count.get() + 1));
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const LEN: usize = 1000;
+
+/// Encoding allocates the output buffer only, once its size is declared
+const BASELINE_ALLOCATIONS: usize = 1;
+
+fn encode<T: AbiEncode>(value: &T) -> Vec<u8> {
+	let mut sink = Sink::with_size(T::HEAD_SIZE / 32, Sink::size_of(value));
+	sink.push(value);
+	sink.finalize().expect("declared capacity is the head of the value")
+}
+
+/// Encodes the arguments as the endpoints and clients generated by `eth_abi` do
+fn encode_args(args: &(u32, Vec<String>, Vec<String>, bool)) -> Vec<u8> {
+	let mut sink = Sink::with_size(
+		4,
+		Sink::size_of(&args.0) + Sink::size_of(&args.1) + Sink::size_of(&args.2) + Sink::size_of(&args.3),
+	);
+	sink.push(&args.0);
+	sink.push(&args.1);
+	sink.push(&args.2);
+	sink.push(&args.3);
+	sink.finalize().expect("declared capacity is the heads of the arguments")
+}
+
+fn assert_allocations<R, F: Fn() -> R>(name: &str, encode: F) {
+	let before = ALLOCATIONS.with(|count| count.get());
+	test::black_box(encode());
+	let allocations = ALLOCATIONS.with(|count| count.get()) - before;
+	assert_eq!(
+		allocations,
+		BASELINE_ALLOCATIONS,
+		"{}: {} allocations per encoding, expected {}",
+		name,
+		allocations,
+		BASELINE_ALLOCATIONS,
+	);
+}
+
+fn bench_encode<T: AbiEncode>(b: &mut Bencher, name: &str, value: T) {
+	assert_allocations(name, || encode(&value));
+	b.iter(|| encode(&value));
+}
+
+#[bench]
+fn encode_uints(b: &mut Bencher) {
+	let value: Vec<U256> = (0..LEN).map(U256::from).collect();
+	bench_encode(b, "uint256[]", value);
+}
+
+#[bench]
+fn encode_strings(b: &mut Bencher) {
+	let value: Vec<String> = (0..LEN).map(|i| format!("string number {}", i)).collect();
+	bench_encode(b, "string[]", value);
+}
+
+#[bench]
+fn encode_nested(b: &mut Bencher) {
+	let value: Vec<Vec<u32>> = (0..LEN).map(|i| vec![i as u32; 3]).collect();
+	bench_encode(b, "uint32[][]", value);
+}
+
+#[bench]
+fn encode_arguments(b: &mut Bencher) {
+	let strings: Vec<String> = (0..LEN).map(|i| format!("string number {}", i)).collect();
+	let args = (7u32, strings.clone(), strings, true);
+	assert_allocations("(uint32,string[],string[],bool)", || encode_args(&args));
+	b.iter(|| encode_args(&args));
+}
@@ -0,0 +1,393 @@
+//! Derivation of the `AbiType` for user defined types
+
+use syn;
+use proc_macro2::{self, Span};
+
+/// Generates the `AbiType` implementation for the given type.
+///
+/// Structs are encoded as tuples of their fields (solidity structs),
+/// fieldless enums are encoded as `uint8` discriminants (solidity enums) and
+/// single-field tuple structs (newtypes) are encoded exactly as the inner type.
+/// The packed encoding (`AbiEncodePacked`) of the structs is the concatenation of the
+/// packed encodings of their fields, like for the tuples.
+/// `FixedAbiType` is implemented as well, when all the fields implement it.
+pub fn impl_abi_type(input: syn::DeriveInput) -> proc_macro2::TokenStream {
+	match input.data {
+		syn::Data::Struct(syn::DataStruct { fields: syn::Fields::Unnamed(ref unnamed), .. })
+			if unnamed.unnamed.len() == 1 => impl_newtype(&input, &unnamed.unnamed[0].ty),
+		syn::Data::Struct(ref data_struct) => impl_struct(&input, &data_struct.fields),
+		syn::Data::Enum(ref data_enum) => impl_enum(&input, data_enum),
+		syn::Data::Union(_) => panic!("AbiType can be derived only for structs and fieldless enums"),
+	}
+}
+
+/// Bound of the `FixedAbiType` implementation on the type of the field.
+///
+/// Higher-ranked, so that it is not rejected as a trivially false bound for the
+/// non-generic types with dynamic fields, which then just don't implement the trait.
+fn fixed_predicate(ty: &syn::Type) -> syn::WherePredicate {
+	parse_quote! { for<'__fixed> #ty: _pwasm_abi::eth::FixedAbiType }
+}
+
+fn impl_struct(input: &syn::DeriveInput, fields: &syn::Fields) -> proc_macro2::TokenStream {
+	let members: Vec<syn::Member> = match *fields {
+		syn::Fields::Named(ref named) => named.named.iter()
+			.map(|field| syn::Member::Named(field.ident.clone().expect("Named field has ident")))
+			.collect(),
+		syn::Fields::Unnamed(ref unnamed) => (0..unnamed.unnamed.len())
+			.map(|idx| syn::Member::Unnamed(syn::Index { index: idx as u32, span: Span::call_site() }))
+			.collect(),
+		syn::Fields::Unit => panic!("AbiType can't be derived for unit structs"),
+	};
+	let field_types: Vec<&syn::Type> = fields.iter().map(|field| &field.ty).collect();
+
+	let name = &input.ident;
+	let mut generics = input.generics.clone();
+	{
+		let where_clause = generics.make_where_clause();
+		for ty in field_types.iter() {
+			where_clause.predicates.push(parse_quote! { #ty: _pwasm_abi::eth::AbiType });
+		}
+	}
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	let mut packed_generics = input.generics.clone();
+	{
+		let where_clause = packed_generics.make_where_clause();
+		for ty in field_types.iter() {
+			where_clause.predicates.push(parse_quote! { #ty: _pwasm_abi::eth::AbiEncodePacked });
+		}
+	}
+	let (packed_impl_generics, packed_ty_generics, packed_where_clause) = packed_generics.split_for_impl();
+
+	let mut fixed_generics = generics.clone();
+	{
+		let where_clause = fixed_generics.make_where_clause();
+		for ty in field_types.iter() {
+			where_clause.predicates.push(fixed_predicate(ty));
+		}
+	}
+	let (fixed_impl_generics, fixed_ty_generics, fixed_where_clause) = fixed_generics.split_for_impl();
+
+	let mut log_generics = input.generics.clone();
+	{
+		let where_clause = log_generics.make_where_clause();
+		for ty in field_types.iter() {
+			where_clause.predicates.push(parse_quote! { #ty: _pwasm_abi::eth::AsLog });
+		}
+	}
+	let (log_impl_generics, log_ty_generics, log_where_clause) = log_generics.split_for_impl();
+
+	let dummy_const = syn::Ident::new(&format!("_IMPL_ABI_TYPE_FOR_{}", name), Span::call_site());
+	let decode_members = &members;
+	let packed_members = &members;
+	let log_members = &members;
+	let encode_members = &members;
+	let fixed_members = &members;
+	let fixed_types = &field_types;
+	let fixed_position_types = &field_types;
+	let decode_fixed_members = &members;
+	let is_fixed_types = &field_types;
+	let name_types = &field_types;
+	let canonical_types = &field_types;
+	let reserve_head_types = &field_types;
+	let head_size_types = &field_types;
+	let size_members = &members;
+	let size_types = &field_types;
+	let size_is_fixed_types = &field_types;
+	let size_head_types = &field_types;
+
+	quote! {
+		#[allow(non_upper_case_globals)]
+		const #dummy_const: () = {
+			extern crate pwasm_abi as _pwasm_abi;
+
+			impl #impl_generics _pwasm_abi::eth::AbiType for #name #ty_generics #where_clause {
+				fn decode(stream: &mut _pwasm_abi::eth::Stream) -> Result<Self, _pwasm_abi::eth::Error> {
+					Ok(#name {
+						#(#decode_members: stream.pop()?,)*
+					})
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::AbiEncode for #name #ty_generics #where_clause {
+				fn encode(&self, sink: &mut _pwasm_abi::eth::Sink) {
+					sink.reserve_head(0 #(+ <#reserve_head_types as _pwasm_abi::eth::AbiEncode>::HEAD_SIZE)*);
+					#(sink.push(&self.#encode_members);)*
+				}
+
+				fn encoded_size(&self) -> usize {
+					0 #(+ if <#size_is_fixed_types as _pwasm_abi::eth::AbiEncode>::IS_FIXED {
+						<#size_head_types as _pwasm_abi::eth::AbiEncode>::HEAD_SIZE
+					} else {
+						32 + <#size_types as _pwasm_abi::eth::AbiEncode>::encoded_size(&self.#size_members)
+					})*
+				}
+
+				fn canonical_name() -> _pwasm_abi::types::String {
+					let members: &[_pwasm_abi::types::String] = &[
+						#(<#name_types as _pwasm_abi::eth::AbiEncode>::canonical_name()),*
+					];
+					let mut result: _pwasm_abi::types::String = "(".into();
+					result.push_str(&members.join(","));
+					result.push(')');
+					result
+				}
+
+				type Canonical = (#(<#canonical_types as _pwasm_abi::eth::AbiEncode>::Canonical,)*);
+
+				const IS_FIXED: bool = true #(&& <#is_fixed_types as _pwasm_abi::eth::AbiEncode>::IS_FIXED)*;
+
+				// offset of the dynamic struct or the heads of the fields of the fixed one
+				const HEAD_SIZE: usize = (Self::IS_FIXED as usize) * (0 #(+ <#head_size_types as _pwasm_abi::eth::AbiEncode>::HEAD_SIZE)*)
+					+ (!Self::IS_FIXED as usize) * 32;
+			}
+
+			impl #impl_generics _pwasm_abi::eth::ArrayItem for #name #ty_generics #where_clause { }
+
+			impl #fixed_impl_generics _pwasm_abi::eth::FixedAbiType for #name #fixed_ty_generics #fixed_where_clause {
+				#[allow(unused_assignments)]
+				fn encode_fixed(&self, target: &mut [u8]) {
+					let mut position = 0;
+					#(
+						let end = position + <#fixed_types as _pwasm_abi::eth::AbiEncode>::HEAD_SIZE;
+						_pwasm_abi::eth::FixedAbiType::encode_fixed(&self.#fixed_members, &mut target[position..end]);
+						position = end;
+					)*
+				}
+
+				fn decode_fixed(stream: &mut _pwasm_abi::eth::Stream) -> Result<Self, _pwasm_abi::eth::Error> {
+					Ok(#name {
+						#(#decode_fixed_members: stream.pop_fixed::<#fixed_position_types>()?,)*
+					})
+				}
+			}
+
+			impl #packed_impl_generics _pwasm_abi::eth::AbiEncodePacked for #name #packed_ty_generics #packed_where_clause {
+				fn encode_packed(&self, sink: &mut _pwasm_abi::eth::PackedSink) {
+					#(sink.push(&self.#packed_members);)*
+				}
+			}
+
+			impl #log_impl_generics _pwasm_abi::eth::AsLog for #name #log_ty_generics #log_where_clause {
+				fn as_log(&self) -> _pwasm_abi::types::H256 {
+					let mut encoded = _pwasm_abi::types::Vec::new();
+					_pwasm_abi::eth::AsLog::encode_indexed(self, &mut encoded);
+					_pwasm_abi::eth::keccak256(&encoded)
+				}
+
+				fn encode_indexed(&self, target: &mut _pwasm_abi::types::Vec<u8>) {
+					#(_pwasm_abi::eth::AsLog::encode_indexed(&self.#log_members, target);)*
+				}
+			}
+		};
+	}
+}
+
+fn impl_newtype(input: &syn::DeriveInput, inner: &syn::Type) -> proc_macro2::TokenStream {
+	let name = &input.ident;
+	let mut generics = input.generics.clone();
+	generics.make_where_clause().predicates.push(parse_quote! { #inner: _pwasm_abi::eth::AbiType });
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+	let mut fixed_generics = generics.clone();
+	fixed_generics.make_where_clause().predicates.push(fixed_predicate(inner));
+	let (fixed_impl_generics, fixed_ty_generics, fixed_where_clause) = fixed_generics.split_for_impl();
+
+	let mut log_generics = input.generics.clone();
+	log_generics.make_where_clause().predicates.push(parse_quote! { #inner: _pwasm_abi::eth::AsLog });
+	let (log_impl_generics, log_ty_generics, log_where_clause) = log_generics.split_for_impl();
+
+	let mut from_log_generics = input.generics.clone();
+	from_log_generics.make_where_clause().predicates.push(parse_quote! { #inner: _pwasm_abi::eth::FromLog });
+	let (from_log_impl_generics, from_log_ty_generics, from_log_where_clause) = from_log_generics.split_for_impl();
+
+	let mut packed_generics = input.generics.clone();
+	packed_generics.make_where_clause().predicates.push(parse_quote! { #inner: _pwasm_abi::eth::AbiEncodePacked });
+	let (packed_impl_generics, packed_ty_generics, packed_where_clause) = packed_generics.split_for_impl();
+
+	let dummy_const = syn::Ident::new(&format!("_IMPL_ABI_TYPE_FOR_{}", name), Span::call_site());
+
+	quote! {
+		#[allow(non_upper_case_globals)]
+		const #dummy_const: () = {
+			extern crate pwasm_abi as _pwasm_abi;
+
+			impl #impl_generics _pwasm_abi::eth::AbiType for #name #ty_generics #where_clause {
+				fn decode(stream: &mut _pwasm_abi::eth::Stream) -> Result<Self, _pwasm_abi::eth::Error> {
+					Ok(#name(stream.pop()?))
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::AbiEncode for #name #ty_generics #where_clause {
+				fn encode(&self, sink: &mut _pwasm_abi::eth::Sink) {
+					_pwasm_abi::eth::AbiEncode::encode(&self.0, sink)
+				}
+
+				fn encoded_size(&self) -> usize {
+					_pwasm_abi::eth::AbiEncode::encoded_size(&self.0)
+				}
+
+				fn canonical_name() -> _pwasm_abi::types::String {
+					<#inner as _pwasm_abi::eth::AbiEncode>::canonical_name()
+				}
+
+				type Canonical = <#inner as _pwasm_abi::eth::AbiEncode>::Canonical;
+
+				const IS_FIXED: bool = <#inner as _pwasm_abi::eth::AbiEncode>::IS_FIXED;
+
+				const HEAD_SIZE: usize = <#inner as _pwasm_abi::eth::AbiEncode>::HEAD_SIZE;
+			}
+
+			impl #impl_generics _pwasm_abi::eth::ArrayItem for #name #ty_generics #where_clause { }
+
+			impl #fixed_impl_generics _pwasm_abi::eth::FixedAbiType for #name #fixed_ty_generics #fixed_where_clause {
+				fn encode_fixed(&self, target: &mut [u8]) {
+					_pwasm_abi::eth::FixedAbiType::encode_fixed(&self.0, target)
+				}
+
+				fn decode_fixed(stream: &mut _pwasm_abi::eth::Stream) -> Result<Self, _pwasm_abi::eth::Error> {
+					Ok(#name(stream.pop_fixed()?))
+				}
+			}
+
+			impl #log_impl_generics _pwasm_abi::eth::AsLog for #name #log_ty_generics #log_where_clause {
+				fn as_log(&self) -> _pwasm_abi::types::H256 {
+					_pwasm_abi::eth::AsLog::as_log(&self.0)
+				}
+
+				fn encode_indexed(&self, target: &mut _pwasm_abi::types::Vec<u8>) {
+					_pwasm_abi::eth::AsLog::encode_indexed(&self.0, target)
+				}
+			}
+
+			impl #from_log_impl_generics _pwasm_abi::eth::FromLog for #name #from_log_ty_generics #from_log_where_clause {
+				fn from_log(topic: &_pwasm_abi::types::H256) -> Result<Self, _pwasm_abi::eth::Error> {
+					Ok(#name(_pwasm_abi::eth::FromLog::from_log(topic)?))
+				}
+			}
+
+			impl #packed_impl_generics _pwasm_abi::eth::AbiEncodePacked for #name #packed_ty_generics #packed_where_clause {
+				fn encode_packed(&self, sink: &mut _pwasm_abi::eth::PackedSink) {
+					_pwasm_abi::eth::AbiEncodePacked::encode_packed(&self.0, sink)
+				}
+			}
+		};
+	}
+}
+
+fn impl_enum(input: &syn::DeriveInput, data_enum: &syn::DataEnum) -> proc_macro2::TokenStream {
+	assert!(
+		data_enum.variants.len() <= 256,
+		"AbiType can't be derived for enums with more than 256 variants (encoded as uint8)"
+	);
+	for variant in data_enum.variants.iter() {
+		match variant.fields {
+			syn::Fields::Unit => {},
+			_ => panic!("AbiType can be derived only for fieldless enums"),
+		}
+		if let Some((_, syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(ref lit), .. }))) = variant.discriminant {
+			assert!(
+				lit.value() <= 255,
+				"Discriminant of the {} variant does not fit uint8",
+				variant.ident
+			);
+		}
+	}
+
+	let name = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	let dummy_const = syn::Ident::new(&format!("_IMPL_ABI_TYPE_FOR_{}", name), Span::call_site());
+	let variants: Vec<&syn::Ident> = data_enum.variants.iter().map(|variant| &variant.ident).collect();
+	let decode_variants = &variants;
+	let decode_results = &variants;
+	let name_repeat = ::std::iter::repeat(name).take(variants.len()).collect::<Vec<_>>();
+	let decode_names = &name_repeat;
+	let decode_result_names = &name_repeat;
+	let encode_variants = &variants;
+	let encode_values = &variants;
+	let encode_names = &name_repeat;
+	let encode_value_names = &name_repeat;
+	let fixed_variants = &variants;
+	let fixed_values = &variants;
+	let fixed_names = &name_repeat;
+	let fixed_value_names = &name_repeat;
+	let packed_variants = &variants;
+	let packed_values = &variants;
+	let packed_names = &name_repeat;
+	let packed_value_names = &name_repeat;
+	let log_variants = &variants;
+	let log_values = &variants;
+	let log_names = &name_repeat;
+	let log_value_names = &name_repeat;
+
+	quote! {
+		#[allow(non_upper_case_globals)]
+		const #dummy_const: () = {
+			extern crate pwasm_abi as _pwasm_abi;
+
+			impl #impl_generics _pwasm_abi::eth::AbiType for #name #ty_generics #where_clause {
+				fn decode(stream: &mut _pwasm_abi::eth::Stream) -> Result<Self, _pwasm_abi::eth::Error> {
+					match stream.pop::<u8>()? {
+						#(value if value == #decode_names::#decode_variants as u8 => Ok(#decode_result_names::#decode_results),)*
+						_ => Err(_pwasm_abi::eth::ErrorKind::InvalidEnumValue.into()),
+					}
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::AbiEncode for #name #ty_generics #where_clause {
+				fn encode(&self, sink: &mut _pwasm_abi::eth::Sink) {
+					let value = match *self {
+						#(#encode_names::#encode_variants => #encode_value_names::#encode_values as u8,)*
+					};
+					sink.push(value);
+				}
+
+				fn canonical_name() -> _pwasm_abi::types::String {
+					"uint8".into()
+				}
+
+				type Canonical = u8;
+
+				const IS_FIXED: bool = true;
+
+				const HEAD_SIZE: usize = 32;
+			}
+
+			impl #impl_generics _pwasm_abi::eth::ArrayItem for #name #ty_generics #where_clause { }
+
+			impl #impl_generics _pwasm_abi::eth::FixedAbiType for #name #ty_generics #where_clause {
+				fn encode_fixed(&self, target: &mut [u8]) {
+					let value = match *self {
+						#(#fixed_names::#fixed_variants => #fixed_value_names::#fixed_values as u8,)*
+					};
+					_pwasm_abi::eth::FixedAbiType::encode_fixed(&value, target)
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::AbiEncodePacked for #name #ty_generics #where_clause {
+				fn encode_packed(&self, sink: &mut _pwasm_abi::eth::PackedSink) {
+					let value = match *self {
+						#(#packed_names::#packed_variants => #packed_value_names::#packed_values as u8,)*
+					};
+					sink.push(value);
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::AsLog for #name #ty_generics #where_clause {
+				fn as_log(&self) -> _pwasm_abi::types::H256 {
+					let value = match *self {
+						#(#log_names::#log_variants => #log_value_names::#log_values as u8,)*
+					};
+					_pwasm_abi::eth::AsLog::as_log(&value)
+				}
+			}
+
+			impl #impl_generics _pwasm_abi::eth::FromLog for #name #ty_generics #where_clause {
+				fn from_log(topic: &_pwasm_abi::types::H256) -> Result<Self, _pwasm_abi::eth::Error> {
+					_pwasm_abi::eth::Stream::new(topic.as_bytes()).pop()
+				}
+			}
+		};
+	}
+}
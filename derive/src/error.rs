@@ -1,6 +1,7 @@
 use std;
 
 use json::JsonError;
+use proc_macro2::Span;
 
 /// The result type for this procedural macro.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -29,6 +30,22 @@ pub enum ErrorKind {
 		/// The index of the malformatted argument.
 		index: usize,
 	},
+	/// When the abi type of a parameter can't be told from its rust type.
+	UnknownAbiType {
+		/// The rust type of the parameter.
+		ty: String,
+		/// The span of the rust type.
+		span: Span,
+	},
+	/// When the `#[abi_type = "..."]` attribute is not a canonical abi type.
+	InvalidAbiType {
+		/// The given abi type.
+		abi_type: String,
+		/// Why the abi type is invalid.
+		reason: String,
+		/// The span of the attribute value.
+		span: Span,
+	},
 }
 
 impl From<JsonError> for Error {
@@ -52,6 +69,15 @@ impl Error {
 		&self.kind
 	}
 
+	/// Returns the span of the code the error points to, if any.
+	pub fn span(&self) -> Option<Span> {
+		match self.kind() {
+			ErrorKind::UnknownAbiType { span, .. } => Some(*span),
+			ErrorKind::InvalidAbiType { span, .. } => Some(*span),
+			_ => None,
+		}
+	}
+
 	/// Returns an error representing that an invalid number of
 	/// arguments passed to `eth_abi` have been found.
 	pub fn invalid_number_of_arguments(found: usize) -> Self {
@@ -65,6 +91,17 @@ impl Error {
 		assert!(index <= 1);
 		Error::from_kind(ErrorKind::MalformattedArgument { index })
 	}
+
+	/// Returns an error representing that the abi type of the parameter of the
+	/// given rust type is unknown.
+	pub fn unknown_abi_type(ty: String, span: Span) -> Self {
+		Error::from_kind(ErrorKind::UnknownAbiType { ty, span })
+	}
+
+	/// Returns an error representing that the given abi type is invalid.
+	pub fn invalid_abi_type(abi_type: String, reason: String, span: Span) -> Self {
+		Error::from_kind(ErrorKind::InvalidAbiType { abi_type, reason, span })
+	}
 }
 
 impl std::fmt::Display for Error {
@@ -81,6 +118,17 @@ impl std::fmt::Display for Error {
 				"found non-identifier argument at index {} passed to eth_abi",
 				index
 			),
+			ErrorKind::UnknownAbiType { ty, .. } => write!(
+				f,
+				"can't tell the abi type of `{}`, declare it with `#[abi_type = \"...\"]` (e.g. `#[abi_type = \"uint256\"]`)",
+				ty
+			),
+			ErrorKind::InvalidAbiType { abi_type, reason, .. } => write!(
+				f,
+				"invalid abi type `{}`: {}",
+				abi_type,
+				reason
+			),
 		}
 	}
 }
@@ -94,7 +142,13 @@ impl std::error::Error for Error {
 			},
 			ErrorKind::MalformattedArgument{ .. } => {
 				"encountered malformatted argument passed to eth_abi: expected identifier (e.g. `Foo`))"
-			}
+			},
+			ErrorKind::UnknownAbiType{ .. } => {
+				"encountered parameter of unknown abi type: expected #[abi_type = \"...\"] attribute"
+			},
+			ErrorKind::InvalidAbiType{ .. } => {
+				"encountered invalid abi type: expected canonical abi type (e.g. `uint256`)"
+			},
 		}
 	}
 }
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use {quote, syn, utils};
 
+use error::{Error, Result};
+use param_type::ParamType;
 use quote::TokenStreamExt;
 use proc_macro2::{self, Span};
 
@@ -7,9 +11,6 @@ use proc_macro2::{self, Span};
 pub struct Event {
 	/// The name of the event.
 	pub name: syn::Ident,
-	/// The canonalized string representation used by the keccak hash
-	/// in order to retrieve the first 4 bytes required upon calling.
-	pub canonical: String,
 	/// The signature of the event.
 	pub method_sig: syn::MethodSig,
 	/// Indexed parameters.
@@ -21,6 +22,12 @@ pub struct Event {
 	pub indexed: Vec<(syn::Pat, syn::Type)>,
 	/// Non-indexed parameters.
 	pub data: Vec<(syn::Pat, syn::Type)>,
+	/// Abi types of the indexed parameters.
+	pub indexed_abi_types: Vec<ParamType>,
+	/// Abi types of the non-indexed parameters.
+	pub data_abi_types: Vec<ParamType>,
+	/// The canonical signature of the event, e.g. `Transfer(address,address,uint256)`.
+	pub signature: String,
 }
 
 /// Represents a function declared in the contracts interface.
@@ -31,16 +38,16 @@ pub struct Event {
 pub struct Signature {
 	/// The name of this signature.
 	pub name: syn::Ident,
-	/// The canonicalized string representation of this signature.
-	pub canonical: String,
 	/// The parameter information of this signature.
 	pub method_sig: syn::MethodSig,
-	/// The function selector hash (4 bytes) of this signature.
-	pub hash: u32,
 	/// The arguments of this signature.
 	pub arguments: Vec<(syn::Pat, syn::Type)>,
+	/// Abi types of the arguments.
+	pub abi_types: Vec<ParamType>,
 	/// The return type of this signature.
 	pub return_types: Vec<syn::Type>,
+	/// Abi types of the return types.
+	pub return_abi_types: Vec<ParamType>,
 	/// If this signature is constant.
 	/// 
 	/// # Note
@@ -104,7 +111,7 @@ impl Item {
 }
 
 impl Interface {
-	pub fn from_item(source: syn::Item) -> Self {
+	pub fn from_item(source: syn::Item, overrides: &utils::AbiTypeOverrides) -> Result<Self> {
 		let item_trait = match source {
 			syn::Item::Trait(item_trait) => item_trait,
 			_ => panic!("Dispatch trait can work with trait declarations only!")
@@ -113,12 +120,14 @@ impl Interface {
 
 		let (constructor_items, other_items) = trait_items
 			.into_iter()
-			.map(Item::from_trait_item)
+			.map(|trait_item| Item::from_trait_item(trait_item, overrides))
+			.collect::<Result<Vec<Item>>>()?
+			.into_iter()
 			.partition::<Vec<Item>, _>(|item| {
 				item.name().map_or(false, |ident| ident.to_string() == "constructor")
 			});
 
-		Interface {
+		Ok(Interface {
 			constructor: constructor_items
 				.into_iter()
 				.next()
@@ -128,7 +137,7 @@ impl Interface {
 				}),
 			name: item_trait.ident.to_string(),
 			items: other_items,
-		}
+		})
 	}
 
 	pub fn items(&self) -> &[Item] {
@@ -148,35 +157,79 @@ fn into_signature(
 	ident: syn::Ident,
 	method_sig: syn::MethodSig,
 	is_constant: bool,
-	is_payable: bool
+	is_payable: bool,
+	abi_types: &HashMap<String, syn::LitStr>,
+	return_abi_type: Option<syn::LitStr>,
 )
-	-> Signature
+	-> Result<Signature>
 {
 	let arguments: Vec<(syn::Pat, syn::Type)> = utils::iter_signature(&method_sig).collect();
-	let return_types: Vec<syn::Type> = match method_sig.decl.output.clone() {
-		syn::ReturnType::Default => Vec::new(),
+	let argument_abi_types = resolve_abi_types(&arguments, abi_types)?;
+	let (return_types, return_abi_types) = match method_sig.decl.output.clone() {
+		syn::ReturnType::Default => (Vec::new(), Vec::new()),
 		syn::ReturnType::Type(_, ty) => {
 			match *ty {
 				syn::Type::Tuple(tuple_type) => {
-					tuple_type.elems.into_iter().collect()
+					let return_types: Vec<syn::Type> = tuple_type.elems.iter().cloned().collect();
+					let return_abi_types = match return_abi_type {
+						// Multiple return values are overridden all at once, as a tuple
+						Some(abi_type) => match utils::resolve_abi_type(&syn::Type::Tuple(tuple_type), Some(&abi_type))? {
+							ParamType::Tuple(members) if members.len() == return_types.len() => members,
+							_ => return Err(Error::invalid_abi_type(
+								abi_type.value(),
+								format!("expected a tuple of the {} returned types", return_types.len()),
+								abi_type.span(),
+							)),
+						},
+						None => return_types
+							.iter()
+							.map(|ty| utils::resolve_abi_type(ty, None))
+							.collect::<Result<Vec<_>>>()?,
+					};
+					(return_types, return_abi_types)
+				},
+				ty => {
+					let return_abi_type = utils::resolve_abi_type(&ty, return_abi_type.as_ref())?;
+					(vec![ty], vec![return_abi_type])
 				},
-				ty => vec![ty],
 			}
 		},
 	};
-	let canonical = utils::canonicalize_fn(&ident, &method_sig);
-	let hash = utils::function_selector(&canonical);
-
-	Signature {
+	Ok(Signature {
 		name: ident,
 		arguments: arguments,
+		abi_types: argument_abi_types,
 		method_sig: method_sig,
-		canonical: canonical,
-		hash: hash,
 		return_types: return_types,
+		return_abi_types: return_abi_types,
 		is_constant: is_constant,
 		is_payable: is_payable,
-	}
+	})
+}
+
+/// Returns the abi types of the given parameters, given the overridden ones by parameter name.
+fn resolve_abi_types(
+	params: &[(syn::Pat, syn::Type)],
+	abi_types: &HashMap<String, syn::LitStr>,
+)
+	-> Result<Vec<ParamType>>
+{
+	params
+		.iter()
+		.map(|&(ref pat, ref ty)| utils::resolve_abi_type(ty, abi_types.get(&utils::pat_name(pat))))
+		.collect()
+}
+
+/// Returns the abi type of the return value overridden with `#[abi_returns = "..."]`, if any.
+fn return_abi_type_attribute(attrs: &[syn::Attribute]) -> Option<syn::LitStr> {
+	attrs.iter().filter_map(|attr| attr.interpret_meta()).find_map(|meta| match meta {
+		syn::Meta::NameValue(syn::MetaNameValue { ref ident, lit: syn::Lit::Str(ref lit_str), .. })
+			if ident == "abi_returns" => Some(lit_str.clone()),
+		syn::Meta::NameValue(ref meta) if meta.ident == "abi_returns" => {
+			panic!("abi_returns attribute should be a string, e.g. #[abi_returns = \"bytes32\"]")
+		},
+		_ => None,
+	})
 }
 
 fn has_attribute(attrs: &[syn::Attribute], name: &str) -> bool {
@@ -189,25 +242,33 @@ fn has_attribute(attrs: &[syn::Attribute], name: &str) -> bool {
 }
 
 impl Item {
-	fn event_from_trait_item(method_sig: syn::MethodSig) -> Self {
+	fn event_from_trait_item(method_sig: syn::MethodSig, abi_types: &HashMap<String, syn::LitStr>) -> Result<Self> {
 		assert!(
 			method_sig.ident != "constructor",
 			"The constructor can't be an event"
 		);
-		let (indexed, non_indexed) = utils::iter_signature(&method_sig)
-			.partition(|&(ref pat, _)| quote! { #pat }.to_string().starts_with("indexed_"));
-		let canonical = utils::canonicalize_fn(&method_sig.ident, &method_sig);
+		let params: Vec<(syn::Pat, syn::Type)> = utils::iter_signature(&method_sig).collect();
+		let param_abi_types = resolve_abi_types(&params, abi_types)?;
+		let signature = utils::signature(&method_sig.ident, &param_abi_types);
+		let (indexed, non_indexed): (Vec<_>, Vec<_>) = params
+			.into_iter()
+			.zip(param_abi_types.into_iter())
+			.partition(|&((ref pat, _), _)| quote! { #pat }.to_string().starts_with("indexed_"));
+		let (indexed, indexed_abi_types) = indexed.into_iter().unzip();
+		let (data, data_abi_types) = non_indexed.into_iter().unzip();
 		let event = Event {
 			name: method_sig.ident.clone(),
-			canonical: canonical,
 			indexed: indexed,
-			data: non_indexed,
+			data: data,
+			indexed_abi_types: indexed_abi_types,
+			data_abi_types: data_abi_types,
+			signature: signature,
 			method_sig: method_sig,
 		};
-		Item::Event(event)
+		Ok(Item::Event(event))
 	}
 
-	fn signature_from_trait_item(method_trait_item: syn::TraitItemMethod) -> Self {
+	fn signature_from_trait_item(method_trait_item: syn::TraitItemMethod, abi_types: &HashMap<String, syn::LitStr>) -> Result<Self> {
 		let constant = has_attribute(&method_trait_item.attrs, "constant");
 		let payable = has_attribute(&method_trait_item.attrs, "payable");
 		assert!(
@@ -221,26 +282,33 @@ impl Item {
 			!(method_trait_item.sig.ident.to_string() == "constructor" && constant),
 			"Constructor can't be constant"
 		);
-		Item::Signature(into_signature(
+		let return_abi_type = return_abi_type_attribute(&method_trait_item.attrs);
+		into_signature(
 			method_trait_item.sig.ident.clone(),
 			method_trait_item.sig,
 			constant,
 			payable,
-		))
+			abi_types,
+			return_abi_type,
+		).map(Item::Signature)
 	}
 
-	pub fn from_trait_item(source: syn::TraitItem) -> Self {
+	pub fn from_trait_item(source: syn::TraitItem, overrides: &utils::AbiTypeOverrides) -> Result<Self> {
 		match source {
 			syn::TraitItem::Method(method_trait_item) => {
 				if method_trait_item.default.is_some() {
-					return Item::Other(syn::TraitItem::Method(method_trait_item))
+					return Ok(Item::Other(syn::TraitItem::Method(method_trait_item)))
 				}
+				let no_overrides = HashMap::new();
+				let abi_types = overrides
+					.get(&method_trait_item.sig.ident.to_string())
+					.unwrap_or(&no_overrides);
 				if has_attribute(&method_trait_item.attrs, "event") {
-					return Self::event_from_trait_item(method_trait_item.sig)
+					return Self::event_from_trait_item(method_trait_item.sig, abi_types)
 				}
-				Self::signature_from_trait_item(method_trait_item)
+				Self::signature_from_trait_item(method_trait_item, abi_types)
 			},
-			trait_item => Item::Other(trait_item)
+			trait_item => Ok(Item::Other(trait_item))
 		}
 	}
 }
@@ -256,28 +324,29 @@ impl quote::ToTokens for Item {
 						name,
 						method_sig,
 						{
-							let keccak = utils::keccak(&event.canonical.as_bytes());
-							let hash_bytes = keccak.as_ref().iter().map(|b| {
-								syn::Lit::Int(syn::LitInt::new(*b as u64, syn::IntSuffix::U8, Span::call_site() ))
-							});
+							let topic = utils::topic_toks(&quote! { ::pwasm_abi }, &event.signature);
 
 							let indexed_pats = event.indexed.iter()
 								.map(|&(ref pat, _)| pat);
 
 							let data_pats = event.data.iter()
 								.map(|&(ref pat, _)| pat);
-
-							let data_pats_count_lit = syn::Lit::Int(
-								syn::LitInt::new(event.data.len() as u64, syn::IntSuffix::Usize, Span::call_site()));
+							let data_size_pats = event.data.iter()
+								.map(|&(ref pat, _)| pat);
+							let data_types = event.data.iter()
+								.map(|&(_, ref ty)| ty);
 
 							quote! {
 								let topics = &[
-									[#(#hash_bytes),*].into(),
+									#topic,
 									#(::pwasm_abi::eth::AsLog::as_log(&#indexed_pats)),*
 								];
 
-								let mut sink = ::pwasm_abi::eth::Sink::new(#data_pats_count_lit);
-								#(sink.push(#data_pats));*;
+								let mut sink = ::pwasm_abi::eth::Sink::with_size(
+									(0 #(+ <#data_types as ::pwasm_abi::eth::AbiEncode>::HEAD_SIZE)*) / 32,
+									0 #(+ ::pwasm_abi::eth::Sink::size_of(&#data_size_pats))*
+								);
+								#(sink.push(&#data_pats));*;
 								let payload = sink.finalize_panicking();
 
 								::pwasm_ethereum::log(topics, &payload);
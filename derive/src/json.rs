@@ -1,6 +1,7 @@
 //! JSON generation
 
-use {items, utils};
+use items;
+use param_type::ParamType;
 use serde_json;
 
 use std::{self, io};
@@ -116,6 +117,36 @@ pub struct Argument {
     pub name: String,
     #[serde(rename = "type")]
     pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Argument>>,
+}
+
+impl Argument {
+    /// Argument of the given name and abi type, with components if type is tuple
+    /// (`tuple`, `tuple[]` and so on).
+    ///
+    /// # Note
+    ///
+    /// Components are unnamed, since only their types are known to `eth_abi`.
+    pub fn new(name: String, abi_type: &ParamType) -> Self {
+        match abi_type.tuple_components() {
+            Some((components, suffix)) => Argument {
+                name: name,
+                type_: format!("tuple{}", suffix),
+                components: Some(
+                    components
+                        .iter()
+                        .map(|component| Argument::new(String::new(), component))
+                        .collect()
+                ),
+            },
+            None => Argument {
+                name: name,
+                type_: abi_type.to_string(),
+                components: None,
+            },
+        }
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -140,9 +171,23 @@ pub struct EventInput {
     pub name: String,
     #[serde(rename = "type")]
     pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Argument>>,
     pub indexed: bool,
 }
 
+impl EventInput {
+    pub fn new(name: String, abi_type: &ParamType, indexed: bool) -> Self {
+        let argument = Argument::new(name, abi_type);
+        EventInput {
+            name: argument.name,
+            type_: argument.type_,
+            components: argument.components,
+            indexed: indexed,
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct EventEntry {
     pub name: String,
@@ -177,23 +222,17 @@ impl<'a> From<&'a items::Event> for EventEntry {
             name: item.name.to_string(),
             inputs: item.indexed
                 .iter()
-                .map(|&(ref pat, ref ty)|
-                    EventInput {
-                        name: quote! { #pat }.to_string(),
-                        type_: utils::canonicalize_type(ty),
-                        indexed: true,
-                    }
-                )
+                .zip(item.indexed_abi_types.iter())
+                .map(|(&(ref pat, _), abi_type)| {
+                    EventInput::new(quote! { #pat }.to_string(), abi_type, true)
+                })
                 .chain(
                     item.data
                         .iter()
-                        .map(|&(ref pat, ref ty)|
-                            EventInput {
-                                name: quote! { #pat }.to_string(),
-                                type_: utils::canonicalize_type(ty),
-                                indexed: false,
-                            }
-                        )
+                        .zip(item.data_abi_types.iter())
+                        .map(|(&(ref pat, _), abi_type)| {
+                            EventInput::new(quote! { #pat }.to_string(), abi_type, false)
+                        })
                     )
                 .collect(),
         }
@@ -206,17 +245,15 @@ impl<'a> From<&'a items::Signature> for FunctionEntry {
             name: item.name.to_string(),
             arguments: item.arguments
                 .iter()
-                .map(|&(ref pat, ref ty)|
-                    Argument {
-                        name: quote! { #pat }.to_string(),
-                        type_: utils::canonicalize_type(ty),
-                    }
-                )
+                .zip(item.abi_types.iter())
+                .map(|(&(ref pat, _), abi_type)| {
+                    Argument::new(quote! { #pat }.to_string(), abi_type)
+                })
                 .collect(),
-            outputs: item.return_types
+            outputs: item.return_abi_types
                 .iter()
                 .enumerate()
-                .map(|(idx, ty)| Argument { name: format!("returnValue{}", idx), type_: utils::canonicalize_type(ty) })
+                .map(|(idx, abi_type)| Argument::new(format!("returnValue{}", idx), abi_type))
                 .collect(),
             constant: item.is_constant,
 			payable: item.is_payable,
@@ -1,6 +1,6 @@
 //! Ethereum (Solidity) derivation for rust contracts (compiled to wasm or otherwise)
 
-#![recursion_limit = "128"]
+#![recursion_limit = "256"]
 #![deny(unused)]
 
 extern crate proc_macro;
@@ -22,8 +22,10 @@ extern crate fixed_hash;
 #[macro_use]
 extern crate serde_derive;
 
+mod abi_type;
 mod error;
 mod items;
+mod param_type;
 mod utils;
 mod json;
 
@@ -118,28 +120,176 @@ impl Args {
 /// Creates an endpoint implementation named `Endpoint2` and a
 /// client implementation named `Client2` for the interface
 /// defined in the `Contract2` trait.
+///
+/// # Abi types
+///
+/// Selectors, event topics and the JSON abi are computed at compile time, when only the
+/// names of the types are known: primitives are recognized by name (e.g. `U256` is `uint256`,
+/// `H256` is `bytes32`), as well as vectors, arrays and tuples of them. Other types (e.g.
+/// type aliases or types deriving `AbiType`) need the `#[abi_type = "..."]` attribute on the
+/// parameter, or `#[abi_returns = "..."]` on the method for the returned ones (a tuple for
+/// multiple return values), and don't compile without it.
+///
+/// Both the recognized and the declared abi types are checked against `AbiEncode::Canonical`
+/// of the types, so that a type declared (or named) as another abi type than it is encoded
+/// as does not compile:
+///
+/// ```ignore
+/// type Amount = U256;
+///
+/// #[eth_abi(Endpoint3)]
+/// trait Contract3 {
+/// 	fn pay(&mut self, #[abi_type = "uint256"] amount: Amount);
+/// 	#[constant]
+/// 	#[abi_returns = "(uint256,bool)"]
+/// 	fn last_payment(&mut self) -> (Amount, bool);
+/// }
+/// ```
+///
+/// Parameters of the `bytes` and `string` types can also be declared as borrowed
+/// `&[u8]` and `&str`, so that the endpoint passes them without copying out of the payload:
+///
+/// ```ignore
+/// #[eth_abi(Endpoint4, Client4)]
+/// trait Contract4 {
+/// 	fn forward(&mut self, data: &[u8], memo: &str);
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn eth_abi(
 	args: proc_macro::TokenStream,
 	input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
 	let args_toks = parse_macro_input!(args as syn::AttributeArgs);
+	let (input, overrides) = utils::strip_abi_type_overrides(input.into());
+	let input: proc_macro::TokenStream = input.into();
 	let input_toks = parse_macro_input!(input as syn::Item);
 
-	let output = match impl_eth_abi(args_toks, input_toks) {
+	let output = match impl_eth_abi(args_toks, input_toks, &overrides) {
 		Ok(output) => output,
-		Err(err) => panic!("[eth_abi] encountered error: {}", err),
+		Err(err) => match err.span() {
+			Some(span) => syn::Error::new(span, err.to_string()).to_compile_error(),
+			None => panic!("[eth_abi] encountered error: {}", err),
+		},
 	};
 
 	output.into()
 }
 
+/// Derive of the `AbiType` for user defined types.
+///
+/// Structs are encoded as Solidity tuples (structs) of their fields, dynamic if
+/// any of the fields is dynamic, and canonicalized as tuples, e.g. `(uint256,address)`.
+///
+/// Fieldless enums are encoded as `uint8` discriminants (Solidity enums), decoding
+/// fails with `Error::InvalidEnumValue` for discriminants not matching any variant.
+///
+/// Single-field tuple structs (newtypes, e.g. `struct TokenAmount(U256)`) are
+/// transparent: encoded, decoded, logged (`AsLog`) and canonicalized exactly as
+/// the inner type.
+///
+/// # Note
+///
+/// `eth_abi` can't tell the abi types of the derived types by their names, so their parameters
+/// need the `#[abi_type = "..."]` attribute (e.g. `#[abi_type = "(address,uint256)"]` for the
+/// `Transfer` below, `#[abi_type = "uint256"]` for `TokenAmount` and `#[abi_type = "uint8"]`
+/// for `State`), checked against `AbiEncode::Canonical` of the derived implementation.
+/// Components of the tuples are unnamed in the JSON abi.
+///
+/// # Example
+///
+/// ```
+/// # #![feature(custom_attribute)]
+/// # extern crate pwasm_abi;
+/// # #[macro_use] extern crate pwasm_abi_derive;
+/// # use pwasm_abi::types::{U256, Address};
+/// #[derive(AbiType)]
+/// struct Transfer {
+/// 	to: Address,
+/// 	amount: U256,
+/// }
+///
+/// #[derive(AbiType)]
+/// struct TokenAmount(U256);
+///
+/// #[derive(AbiType)]
+/// enum State {
+/// 	Created,
+/// 	Locked,
+/// 	Inactive,
+/// }
+/// # fn main() { }
+/// ```
+#[proc_macro_derive(AbiType)]
+pub fn derive_abi_type(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let input_toks = parse_macro_input!(input as syn::DeriveInput);
+	abi_type::impl_abi_type(input_toks).into()
+}
+
+/// Function selector of the given canonical signature as `u32` literal (the first 4 bytes
+/// of its keccak256 hash, big-endian), computed at compile time like the selectors of the
+/// `eth_abi` endpoints (see `pwasm_abi::eth::selector` for runtime).
+///
+/// Requires `#![feature(proc_macro_hygiene)]` in the expression position.
+///
+/// # Example
+///
+/// ```ignore
+/// const BAZ: u32 = selector!("baz(uint32,bool)"); // 0xcdcd77c0
+/// assert_eq!(&payload[0..4], &BAZ.to_be_bytes());
+/// ```
+#[proc_macro]
+pub fn selector(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let signature = parse_macro_input!(input as syn::LitStr).value();
+	check_canonical_signature("selector", &signature);
+
+	let hash = syn::LitInt::new(utils::function_selector(&signature) as u64, syn::IntSuffix::U32, Span::call_site());
+	(quote! { #hash }).into()
+}
+
+/// Event topic (topic0 of the log) of the given canonical event signature as `H256`,
+/// computed at compile time like the topics of the `#[event]` methods (see
+/// `pwasm_abi::eth::event_topic` for runtime).
+///
+/// Requires `#![feature(proc_macro_hygiene)]` in the expression position.
+///
+/// # Example
+///
+/// ```ignore
+/// let topic: H256 = topic!("Transfer(address,address,uint256)");
+/// ```
+#[proc_macro]
+pub fn topic(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let signature = parse_macro_input!(input as syn::LitStr).value();
+	check_canonical_signature("topic", &signature);
+
+	utils::topic_toks(&quote! { ::pwasm_abi }, &signature).into()
+}
+
+/// Panics if the signature given to the `macro_name` is not canonical
+/// (e.g. contains whitespace or parameter names), since it would silently hash differently.
+fn check_canonical_signature(macro_name: &str, signature: &str) {
+	let open = signature.find('(');
+	assert!(
+		open.map_or(false, |open| open > 0)
+			&& signature.ends_with(')')
+			&& !signature.chars().any(|c| c.is_whitespace()),
+		"[{}] expected canonical signature (e.g. `transfer(address,uint256)`), found `{}`",
+		macro_name,
+		signature
+	);
+}
+
 /// Implementation of `eth_abi`.
 ///
 /// This convenience function is mainly used to better handle the results of token stream.
-fn impl_eth_abi(args: syn::AttributeArgs, input: syn::Item) -> Result<proc_macro2::TokenStream> {
+fn impl_eth_abi(
+	args: syn::AttributeArgs,
+	input: syn::Item,
+	overrides: &utils::AbiTypeOverrides,
+) -> Result<proc_macro2::TokenStream> {
 	let args = Args::from_attribute_args(args)?;
-	let intf = items::Interface::from_item(input);
+	let intf = items::Interface::from_item(input, overrides)?;
 
 	write_json_abi(&intf)?;
 
@@ -164,6 +314,8 @@ fn generate_eth_endpoint_wrapper(
 	let mod_name_ident = syn::Ident::new(&mod_name, Span::call_site());
 	// FIXME: <<<
 
+	let selectors_toks = generate_selectors(intf);
+	let abi_type_checks_toks = generate_abi_type_checks(intf);
 	let endpoint_toks = generate_eth_endpoint(endpoint_name, intf);
 	let endpoint_ident = syn::Ident::new(endpoint_name, Span::call_site());
 
@@ -173,8 +325,11 @@ fn generate_eth_endpoint_wrapper(
 		mod #mod_name_ident {
 			extern crate pwasm_ethereum;
 			extern crate pwasm_abi;
-			use pwasm_abi::types::{H160, H256, U256, Address, Vec, String};
+			use pwasm_abi::types::{H160, H256, U256, I256, Address, Vec, String};
+			use super::*;
 			use super::#name_ident_use;
+			#selectors_toks
+			#abi_type_checks_toks
 			#endpoint_toks
 		}
 		pub use self::#mod_name_ident::#endpoint_ident;
@@ -196,6 +351,8 @@ fn generate_eth_endpoint_and_client_wrapper(
 	let mod_name_ident = syn::Ident::new(&mod_name, Span::call_site());
 	// FIXME: <<<
 
+	let selectors_toks = generate_selectors(&intf);
+	let abi_type_checks_toks = generate_abi_type_checks(&intf);
 	let endpoint_toks = generate_eth_endpoint(endpoint_name, &intf);
 	let client_toks = generate_eth_client(client_name, &intf);
 	let endpoint_name_ident = syn::Ident::new(endpoint_name, Span::call_site());
@@ -207,8 +364,11 @@ fn generate_eth_endpoint_and_client_wrapper(
 		mod #mod_name_ident {
 			extern crate pwasm_ethereum;
 			extern crate pwasm_abi;
-			use pwasm_abi::types::{H160, H256, U256, Address, Vec, String};
+			use pwasm_abi::types::{H160, H256, U256, I256, Address, Vec, String};
+			use super::*;
 			use super::#name_ident_use;
+			#selectors_toks
+			#abi_type_checks_toks
 			#endpoint_toks
 			#client_toks
 		}
@@ -217,6 +377,81 @@ fn generate_eth_endpoint_and_client_wrapper(
 	})
 }
 
+/// Returns the identifier of the constant selector of the given signature.
+fn selector_ident(signature: &items::Signature) -> syn::Ident {
+	syn::Ident::new(&format!("{}_SELECTOR", signature.name.to_string().to_uppercase()), Span::call_site())
+}
+
+/// Generates the constant selectors of the methods, shared by the endpoint and the client.
+fn generate_selectors(intf: &items::Interface) -> proc_macro2::TokenStream {
+	let selectors = intf.items().iter().filter_map(|item| match *item {
+		Item::Signature(ref signature) => {
+			let selector_ident = selector_ident(signature);
+			let canonical_signature = utils::signature(&signature.name, &signature.abi_types);
+			let selector = syn::LitInt::new(
+				utils::function_selector(&canonical_signature) as u64,
+				syn::IntSuffix::U32,
+				Span::call_site(),
+			);
+			Some(quote! {
+				#[doc = #canonical_signature]
+				const #selector_ident: u32 = #selector;
+			})
+		},
+		_ => None,
+	});
+	quote! { #(#selectors)* }
+}
+
+/// Generates the functions checking the abi types of the parameters and return types
+/// against `AbiEncode::Canonical` of their types (see `utils::abi_type_checks`).
+fn generate_abi_type_checks(intf: &items::Interface) -> proc_macro2::TokenStream {
+	fn signature_checks(signature: &items::Signature) -> proc_macro2::TokenStream {
+		let check_ident = syn::Ident::new(&format!("__abi_types_of_{}", signature.name), Span::call_site());
+		let params: Vec<(&syn::Type, &param_type::ParamType)> = signature.arguments
+			.iter()
+			.map(|&(_, ref ty)| ty)
+			.chain(signature.return_types.iter())
+			.zip(signature.abi_types.iter().chain(signature.return_abi_types.iter()))
+			.collect();
+		utils::abi_type_checks(&quote! { pwasm_abi }, &check_ident, &signature.method_sig.decl.generics, &params)
+	}
+
+	let checks = intf.constructor().into_iter().map(signature_checks).chain(
+		intf.items().iter().filter_map(|item| match *item {
+			Item::Signature(ref signature) => Some(signature_checks(signature)),
+			Item::Event(ref event) => {
+				let check_ident = syn::Ident::new(&format!("__abi_types_of_{}", event.name), Span::call_site());
+				let params: Vec<(&syn::Type, &param_type::ParamType)> = event.indexed
+					.iter()
+					.chain(event.data.iter())
+					.map(|&(_, ref ty)| ty)
+					.zip(event.indexed_abi_types.iter().chain(event.data_abi_types.iter()))
+					.collect();
+				Some(utils::abi_type_checks(&quote! { pwasm_abi }, &check_ident, &event.method_sig.decl.generics, &params))
+			},
+			Item::Other(_) => None,
+		})
+	);
+	quote! { #(#checks)* }
+}
+
+/// Returns identifiers to bind the members of the returned tuple to,
+/// or `None` if the given signature does not return a tuple.
+fn tuple_return_idents(signature: &items::Signature) -> Option<Vec<syn::Ident>> {
+	match signature.method_sig.decl.output {
+		syn::ReturnType::Type(_, ref ty) => match **ty {
+			syn::Type::Tuple(_) => Some(
+				(0..signature.return_types.len())
+					.map(|idx| syn::Ident::new(&format!("result{}", idx), Span::call_site()))
+					.collect()
+			),
+			_ => None,
+		},
+		syn::ReturnType::Default => None,
+	}
+}
+
 fn generate_eth_client(client_name: &str, intf: &items::Interface) -> proc_macro2::TokenStream {
 	let client_ctor = intf.constructor().map(
 		|signature| utils::produce_signature(
@@ -233,31 +468,49 @@ fn generate_eth_client(client_name: &str, intf: &items::Interface) -> proc_macro
 	let calls: Vec<proc_macro2::TokenStream> = intf.items().iter().filter_map(|item| {
 		match *item {
 			Item::Signature(ref signature)  => {
-				let hash_literal = syn::Lit::Int(
-					syn::LitInt::new(signature.hash as u64, syn::IntSuffix::U32, Span::call_site()));
-				let argument_push: Vec<proc_macro2::TokenStream> = utils::iter_signature(&signature.method_sig)
-					.map(|(pat, _)| quote! { sink.push(#pat); })
+				let selector_ident = selector_ident(signature);
+				let argument_pats: Vec<syn::Pat> = utils::iter_signature(&signature.method_sig)
+					.map(|(pat, _)| pat)
 					.collect();
-				let argument_count_literal = syn::Lit::Int(
-					syn::LitInt::new(argument_push.len() as u64, syn::IntSuffix::Usize, Span::call_site()));
+				let argument_types: Vec<syn::Type> = utils::iter_signature(&signature.method_sig)
+					.map(|(_, ty)| ty)
+					.collect();
+				let argument_size_pats = &argument_pats;
+				let argument_push_pats = &argument_pats;
 
 				let result_instance = match signature.method_sig.decl.output {
 					syn::ReturnType::Default => quote!{
 						let mut result = Vec::new();
 					},
-					syn::ReturnType::Type(_, _) => quote!{
-						let mut result = [0u8; 32];
+					syn::ReturnType::Type(_, ref ty) => quote!{
+						// the size of the dynamic output is not known until it is decoded
+						let result_len = if <#ty as pwasm_abi::eth::AbiEncode>::IS_FIXED {
+							<#ty as pwasm_abi::eth::AbiEncode>::HEAD_SIZE
+						} else {
+							self.result_capacity.unwrap_or(Self::DEFAULT_RESULT_CAPACITY)
+						};
+						let mut result = Vec::with_capacity(result_len);
+						result.resize(result_len, 0u8);
 					},
 				};
 
 				let result_pop = match signature.method_sig.decl.output {
 					syn::ReturnType::Default => None,
-					syn::ReturnType::Type(_, _) => Some(
-						quote!{
-							let mut stream = pwasm_abi::eth::Stream::new(&result);
-							stream.pop().expect("failed decode call output")
-						}
-					),
+					syn::ReturnType::Type(_, ref ty) => {
+						// Multiple return values are encoded as the members of the tuple, like arguments
+						let result_value = match tuple_return_idents(signature) {
+							Some(_) => quote! {
+								<#ty as pwasm_abi::eth::AbiType>::decode(&mut stream).expect("failed decode call output")
+							},
+							None => quote! { stream.pop::<#ty>().expect("failed decode call output") },
+						};
+						Some(
+							quote!{
+								let mut stream = pwasm_abi::eth::Stream::new(&result);
+								#result_value
+							}
+						)
+					},
 				};
 
 				Some(utils::produce_signature(
@@ -266,16 +519,13 @@ fn generate_eth_client(client_name: &str, intf: &items::Interface) -> proc_macro
 					quote!{
 						#![allow(unused_mut)]
 						#![allow(unused_variables)]
-						let mut payload = Vec::with_capacity(4 + #argument_count_literal * 32);
-						payload.push((#hash_literal >> 24) as u8);
-						payload.push((#hash_literal >> 16) as u8);
-						payload.push((#hash_literal >> 8) as u8);
-						payload.push(#hash_literal as u8);
-
-						let mut sink = pwasm_abi::eth::Sink::new(#argument_count_literal);
-						#(#argument_push)*
-
-						sink.drain_to(&mut payload);
+						let mut sink = pwasm_abi::eth::Sink::with_prefix(
+							&[(#selector_ident >> 24) as u8, (#selector_ident >> 16) as u8, (#selector_ident >> 8) as u8, #selector_ident as u8],
+							(0 #(+ <#argument_types as pwasm_abi::eth::AbiEncode>::HEAD_SIZE)*) / 32,
+							0 #(+ pwasm_abi::eth::Sink::size_of(&#argument_size_pats))*
+						);
+						#(sink.push(&#argument_push_pats);)*
+						let payload = sink.finalize_panicking();
 
 						#result_instance
 
@@ -308,17 +558,29 @@ fn generate_eth_client(client_name: &str, intf: &items::Interface) -> proc_macro
 			gas: Option<u64>,
 			address: Address,
 			value: Option<U256>,
+			result_capacity: Option<usize>,
 		}
 
 		impl #client_ident {
+			/// Default size of the buffer for the output of the calls returning dynamic values
+			pub const DEFAULT_RESULT_CAPACITY: usize = 4096;
+
 			pub fn new(address: Address) -> Self {
 				#client_ident {
 					gas: None,
 					address: address,
 					value: None,
+					result_capacity: None,
 				}
 			}
 
+			/// Size of the buffer for the output of the calls returning dynamic values
+			/// (`DEFAULT_RESULT_CAPACITY` bytes by default), longer outputs fail to decode
+			pub fn result_capacity(mut self, bytes: usize) -> Self {
+				self.result_capacity = Some(bytes);
+				self
+			}
+
 			pub fn gas(mut self, gas: u64) -> Self {
 				self.gas = Some(gas);
 				self
@@ -349,16 +611,39 @@ fn generate_eth_endpoint(endpoint_name: &str, intf: &items::Interface) -> proc_m
 		}
 	}
 
+	// Decodes the arguments into the `arg0`, `arg1`, ... variables, checking for trailing
+	// bytes in the strict mode.
+	fn decode_args_toks(signature: &items::Signature, payload: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+		let arg_types = signature.arguments.iter().map(|&(_, ref ty)| quote! { #ty });
+		let arg_names = signature.arguments.iter().map(|&(ref pat, _)| utils::pat_name(pat));
+		let method_names = ::std::iter::repeat(signature.name.to_string());
+		let arg_idents = arg_idents(signature);
+		quote! {
+			let mut stream = pwasm_abi::eth::Stream::new(#payload);
+			#(
+				let #arg_idents = stream.pop::<#arg_types>()
+					.map_err(|err| err.in_argument(#method_names, #arg_names))
+					.expect("argument decoding failed");
+			)*
+			stream.finish().expect("argument decoding failed");
+		}
+	}
+
+	fn arg_idents(signature: &items::Signature) -> Vec<syn::Ident> {
+		(0..signature.arguments.len())
+			.map(|idx| syn::Ident::new(&format!("arg{}", idx), Span::call_site()))
+			.collect()
+	}
+
 	let ctor_branch = intf.constructor().map(
 		|signature| {
-			let arg_types = signature.arguments.iter().map(|&(_, ref ty)| quote! { #ty });
+			let decode_args = decode_args_toks(signature, quote! { payload });
+			let arg_idents = arg_idents(signature);
 			let check_value_if_payable = check_value_if_payable_toks(signature.is_payable);
 			quote! {
 				#check_value_if_payable
-				let mut stream = pwasm_abi::eth::Stream::new(payload);
-				self.inner.constructor(
-					#(stream.pop::<#arg_types>().expect("argument decoding failed")),*
-				);
+				#decode_args
+				self.inner.constructor(#(#arg_idents),*);
 			}
 		}
 	);
@@ -366,34 +651,43 @@ fn generate_eth_endpoint(endpoint_name: &str, intf: &items::Interface) -> proc_m
 	let branches: Vec<proc_macro2::TokenStream> = intf.items().iter().filter_map(|item| {
 		match *item {
 			Item::Signature(ref signature)  => {
-				let hash_literal = syn::Lit::Int(
-					syn::LitInt::new(signature.hash as u64, syn::IntSuffix::U32, Span::call_site()));
+				let selector_ident = selector_ident(signature);
 				let ident = &signature.name;
-				let arg_types = signature.arguments.iter().map(|&(_, ref ty)| quote! { #ty });
+				let decode_args = decode_args_toks(signature, quote! { method_payload });
+				let arg_idents = arg_idents(signature);
 				let check_value_if_payable = check_value_if_payable_toks(signature.is_payable);
 				if !signature.return_types.is_empty() {
-					let return_count_literal = syn::Lit::Int(
-						syn::LitInt::new(signature.return_types.len() as u64, syn::IntSuffix::Usize, Span::call_site()));
+					// Multiple return values are encoded one by one rather than as a single tuple
+					let (result_bind, result_idents) = match tuple_return_idents(signature) {
+						Some(idents) => {
+							let bind_idents = &idents;
+							(Some(quote! { let (#(#bind_idents,)*) = result; }), idents.clone())
+						},
+						None => (None, vec![syn::Ident::new("result", Span::call_site())]),
+					};
+					let result_types = &signature.return_types;
+					let result_size_idents = &result_idents;
+					let result_push_idents = &result_idents;
 					Some(quote! {
-						#hash_literal => {
+						#selector_ident => {
 							#check_value_if_payable
-							let mut stream = pwasm_abi::eth::Stream::new(method_payload);
-							let result = inner.#ident(
-								#(stream.pop::<#arg_types>().expect("argument decoding failed")),*
+							#decode_args
+							let result = inner.#ident(#(#arg_idents),*);
+							#result_bind
+							let mut sink = pwasm_abi::eth::Sink::with_size(
+								(0 #(+ <#result_types as pwasm_abi::eth::AbiEncode>::HEAD_SIZE)*) / 32,
+								0 #(+ pwasm_abi::eth::Sink::size_of(&#result_size_idents))*
 							);
-							let mut sink = pwasm_abi::eth::Sink::new(#return_count_literal);
-							sink.push(result);
+							#(sink.push(#result_push_idents);)*
 							sink.finalize_panicking()
 						}
 					})
 				} else {
 					Some(quote! {
-						#hash_literal => {
+						#selector_ident => {
 							#check_value_if_payable
-							let mut stream = pwasm_abi::eth::Stream::new(method_payload);
-							inner.#ident(
-								#(stream.pop::<#arg_types>().expect("argument decoding failed")),*
-							);
+							#decode_args
+							inner.#ident(#(#arg_idents),*);
 							Vec::new()
 						}
 					})
@@ -447,7 +741,7 @@ fn generate_eth_endpoint(endpoint_name: &str, intf: &items::Interface) -> proc_m
 				let method_payload = &payload[4..];
 
 				match method_id {
-					#(#branches,)*
+					#(#branches)*
 					_ => panic!("Invalid method signature"),
 				}
 			}
@@ -0,0 +1,306 @@
+//! Abi types of the parameters, as written in the signatures and the JSON abi
+
+use std::fmt;
+
+use syn;
+use proc_macro2::TokenStream;
+
+/// Abi type of a parameter (e.g. `uint256`, `bytes32[]` or `(address,string)`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamType {
+	/// `uintN`
+	Uint(usize),
+	/// `intN`
+	Int(usize),
+	/// `address`
+	Address,
+	/// `bool`
+	Bool,
+	/// `bytesN`
+	FixedBytes(usize),
+	/// `bytes`
+	Bytes,
+	/// `string`
+	String,
+	/// `T[]`
+	Array(Box<ParamType>),
+	/// `T[k]`
+	FixedArray(Box<ParamType>, usize),
+	/// `(T1,T2,...)`
+	Tuple(Vec<ParamType>),
+}
+
+impl fmt::Display for ParamType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ParamType::Uint(bits) => write!(f, "uint{}", bits),
+			ParamType::Int(bits) => write!(f, "int{}", bits),
+			ParamType::Address => write!(f, "address"),
+			ParamType::Bool => write!(f, "bool"),
+			ParamType::FixedBytes(len) => write!(f, "bytes{}", len),
+			ParamType::Bytes => write!(f, "bytes"),
+			ParamType::String => write!(f, "string"),
+			ParamType::Array(ref item) => write!(f, "{}[]", item),
+			ParamType::FixedArray(ref item, len) => write!(f, "{}[{}]", item, len),
+			ParamType::Tuple(ref members) => {
+				write!(f, "(")?;
+				for (i, member) in members.iter().enumerate() {
+					if i != 0 { write!(f, ",")?; }
+					write!(f, "{}", member)?;
+				}
+				write!(f, ")")
+			},
+		}
+	}
+}
+
+impl ParamType {
+	/// Parses the canonical abi type (as in the function signatures), returning the
+	/// reason if it is not one.
+	pub fn parse(source: &str) -> Result<ParamType, String> {
+		let mut parser = Parser { source: source, position: 0 };
+		let param_type = parser.param_type()?;
+		match parser.peek() {
+			None => Ok(param_type),
+			Some(c) => Err(format!("unexpected `{}` at {}", c, parser.position)),
+		}
+	}
+
+	/// Returns the components of the tuple in this type, along with the array suffix
+	/// of the type (e.g. `[]` for `(uint32,bool)[]`), or `None` if it is not a tuple.
+	pub fn tuple_components(&self) -> Option<(&[ParamType], String)> {
+		match *self {
+			ParamType::Tuple(ref members) => Some((members, String::new())),
+			ParamType::Array(ref item) => item.tuple_components().map(|(members, suffix)| (members, suffix + "[]")),
+			ParamType::FixedArray(ref item, len) => {
+				item.tuple_components().map(|(members, suffix)| (members, format!("{}[{}]", suffix, len)))
+			},
+			_ => None,
+		}
+	}
+
+	/// Returns the rust type standing for this abi type (`AbiEncode::Canonical`), given the
+	/// path to the `pwasm_abi` crate, or `None` if no type implements it (e.g. `uint24`).
+	pub fn canonical_type(&self, krate: &TokenStream) -> Option<TokenStream> {
+		Some(match *self {
+			ParamType::Uint(8) => quote! { u8 },
+			ParamType::Uint(16) => quote! { u16 },
+			ParamType::Uint(32) => quote! { u32 },
+			ParamType::Uint(64) => quote! { u64 },
+			ParamType::Uint(128) => quote! { u128 },
+			ParamType::Uint(256) => quote! { #krate::types::U256 },
+			ParamType::Int(8) => quote! { i8 },
+			ParamType::Int(16) => quote! { i16 },
+			ParamType::Int(32) => quote! { i32 },
+			ParamType::Int(64) => quote! { i64 },
+			ParamType::Int(128) => quote! { i128 },
+			ParamType::Int(256) => quote! { #krate::types::I256 },
+			ParamType::Uint(_) | ParamType::Int(_) => return None,
+			ParamType::Address => quote! { #krate::types::H160 },
+			ParamType::Bool => quote! { bool },
+			ParamType::FixedBytes(len) => quote! { [u8; #len] },
+			ParamType::Bytes => quote! { #krate::eth::canonical::Bytes },
+			ParamType::String => quote! { #krate::eth::canonical::Str },
+			ParamType::Array(ref item) => {
+				let item = item.canonical_type(krate)?;
+				quote! { #krate::eth::canonical::Array<#item> }
+			},
+			ParamType::FixedArray(ref item, len) => {
+				let item = item.canonical_type(krate)?;
+				quote! { [#item; #len] }
+			},
+			ParamType::Tuple(ref members) => {
+				let members = members
+					.iter()
+					.map(|member| member.canonical_type(krate))
+					.collect::<Option<Vec<_>>>()?;
+				quote! { (#(#members,)*) }
+			},
+		})
+	}
+
+	/// Returns the abi type of the given rust type, as far as it can be told from its name:
+	/// primitives are recognized by name (e.g. `U256` is `uint256`, `H256` is `bytes32`),
+	/// as well as vectors, arrays, tuples and borrowed slices of them.
+	///
+	/// # Note
+	///
+	/// `eth_abi` checks the described types against `AbiEncode::Canonical` at compile time,
+	/// so that a type named as a primitive but encoded otherwise is rejected.
+	pub fn describe(ty: &syn::Type) -> Option<ParamType> {
+		match ty {
+			syn::Type::Path(type_path) if type_path.qself.is_none() => {
+				describe_path_segment(*type_path.path.segments.last()?.value())
+			},
+			syn::Type::Array(type_array) => {
+				let len = int_const_expr(&type_array.len)?;
+				// Special cases for `bytesN`
+				if is_u8(&type_array.elem) {
+					return Some(ParamType::FixedBytes(len));
+				}
+				Some(ParamType::FixedArray(Box::new(ParamType::describe(&type_array.elem)?), len))
+			},
+			syn::Type::Tuple(type_tuple) if !type_tuple.elems.is_empty() => {
+				type_tuple.elems
+					.iter()
+					.map(ParamType::describe)
+					.collect::<Option<Vec<_>>>()
+					.map(ParamType::Tuple)
+			},
+			// Borrowed `&[u8]` and `&str`, decoded without copying
+			syn::Type::Reference(type_reference) if type_reference.mutability.is_none() => {
+				match *type_reference.elem {
+					syn::Type::Slice(ref type_slice) if is_u8(&type_slice.elem) => Some(ParamType::Bytes),
+					syn::Type::Path(ref type_path) if type_path.qself.is_none() && type_path.path.is_ident("str") => {
+						Some(ParamType::String)
+					},
+					_ => None,
+				}
+			},
+			_ => None,
+		}
+	}
+}
+
+fn int_const_expr(expr: &syn::Expr) -> Option<usize> {
+	match expr {
+		syn::Expr::Lit(syn::ExprLit{lit: syn::Lit::Int(lit_int), ..}) => Some(lit_int.value() as usize),
+		_ => None,
+	}
+}
+
+fn is_u8(ty: &syn::Type) -> bool {
+	match ty {
+		syn::Type::Path(type_path) => {
+			type_path.qself.is_none() && type_path.path.segments.last().unwrap().value().ident == "u8"
+		},
+		_ => false,
+	}
+}
+
+fn describe_vec(args: &syn::PathArguments) -> Option<ParamType> {
+	match args {
+		syn::PathArguments::AngleBracketed(gen_args) => {
+			match gen_args.args.last().map(|arg| arg.into_value()) {
+				Some(&syn::GenericArgument::Type(ref elem_type)) if is_u8(elem_type) => Some(ParamType::Bytes),
+				Some(&syn::GenericArgument::Type(ref elem_type)) => {
+					ParamType::describe(elem_type).map(|item| ParamType::Array(Box::new(item)))
+				},
+				_ => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+fn describe_path_segment(seg: &syn::PathSegment) -> Option<ParamType> {
+	Some(match seg.ident.to_string().as_str() {
+		"u8" => ParamType::Uint(8),
+		"i8" => ParamType::Int(8),
+		"u16" => ParamType::Uint(16),
+		"i16" => ParamType::Int(16),
+		"u32" => ParamType::Uint(32),
+		"i32" => ParamType::Int(32),
+		"u64" => ParamType::Uint(64),
+		"i64" => ParamType::Int(64),
+		"u128" => ParamType::Uint(128),
+		"i128" => ParamType::Int(128),
+		"U256" => ParamType::Uint(256),
+		"I256" => ParamType::Int(256),
+		"H256" => ParamType::FixedBytes(32),
+		"H160" | "Address" => ParamType::Address,
+		"String" => ParamType::String,
+		"bool" => ParamType::Bool,
+		"Vec" => return describe_vec(&seg.arguments),
+		_ => return None,
+	})
+}
+
+/// Parser of the canonical abi types.
+struct Parser<'a> {
+	source: &'a str,
+	position: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn peek(&self) -> Option<char> {
+		self.source[self.position..].chars().next()
+	}
+
+	fn eat(&mut self, expected: char) -> bool {
+		if self.peek() == Some(expected) {
+			self.position += expected.len_utf8();
+			true
+		} else {
+			false
+		}
+	}
+
+	fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+		let start = self.position;
+		while let Some(c) = self.peek() {
+			if !predicate(c) { break; }
+			self.position += c.len_utf8();
+		}
+		&self.source[start..self.position]
+	}
+
+	fn param_type(&mut self) -> Result<ParamType, String> {
+		let mut param_type = if self.eat('(') {
+			let mut members = Vec::new();
+			if !self.eat(')') {
+				loop {
+					members.push(self.param_type()?);
+					if self.eat(')') { break; }
+					if !self.eat(',') {
+						return Err(format!("expected `,` or `)` at {}", self.position));
+					}
+				}
+			}
+			ParamType::Tuple(members)
+		} else {
+			let position = self.position;
+			let name = self.take_while(|c| c.is_ascii_alphanumeric());
+			elementary_type(name).map_err(|reason| format!("{} at {}", reason, position))?
+		};
+		while self.eat('[') {
+			let position = self.position;
+			let len = self.take_while(|c| c.is_ascii_digit());
+			if !self.eat(']') {
+				return Err(format!("expected `]` at {}", self.position));
+			}
+			param_type = match len.parse() {
+				_ if len.is_empty() => ParamType::Array(Box::new(param_type)),
+				Ok(parsed) if !len.starts_with('0') => ParamType::FixedArray(Box::new(param_type), parsed),
+				_ => return Err(format!("invalid array length `{}` at {}", len, position)),
+			};
+		}
+		Ok(param_type)
+	}
+}
+
+/// Returns the elementary (non-tuple, non-array) abi type of the given name.
+fn elementary_type(name: &str) -> Result<ParamType, String> {
+	let bits = |prefix: &str| -> Result<usize, String> {
+		match name[prefix.len()..].parse::<usize>() {
+			Ok(bits) if bits > 0 && bits <= 256 && bits % 8 == 0 && !name[prefix.len()..].starts_with('0') => Ok(bits),
+			_ => Err(format!("invalid `{}`, expected {}8, {}16, ... {}256", name, prefix, prefix, prefix)),
+		}
+	};
+	match name {
+		"address" => Ok(ParamType::Address),
+		"bool" => Ok(ParamType::Bool),
+		"bytes" => Ok(ParamType::Bytes),
+		"string" => Ok(ParamType::String),
+		"uint" | "int" => Err(format!("`{}` is not canonical, expected `{}256`", name, name)),
+		"" => Err("expected type".to_owned()),
+		_ if name.starts_with("uint") => bits("uint").map(ParamType::Uint),
+		_ if name.starts_with("int") => bits("int").map(ParamType::Int),
+		_ if name.starts_with("bytes") => match name["bytes".len()..].parse::<usize>() {
+			Ok(len) if len > 0 && len <= 32 && !name["bytes".len()..].starts_with('0') => Ok(ParamType::FixedBytes(len)),
+			_ => Err(format!("invalid `{}`, expected bytes1, bytes2, ... bytes32", name)),
+		},
+		_ => Err(format!("unknown type `{}`", name)),
+	}
+}
+
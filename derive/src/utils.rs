@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+
 use {syn, quote};
+use error::{Error, Result};
+use param_type::ParamType;
+use proc_macro2::{self, Delimiter, Group, Span, TokenStream, TokenTree};
 use tiny_keccak::Keccak;
 use byteorder::{BigEndian, ByteOrder};
 
@@ -68,108 +73,167 @@ pub fn produce_signature<T: quote::ToTokens>(
 	}
 }
 
-fn push_int_const_expr(target: &mut String, expr: &syn::Expr) {
-	match expr {
-		syn::Expr::Lit(syn::ExprLit{lit: syn::Lit::Int(lit_int), ..}) => {
-			target.push_str(&format!("{}", lit_int.value()))
-		}
-		_ => panic!("Cannot use something other than integer literal in this constant expression"),
+/// Overridden abi types of the parameters (`#[abi_type = "bytes32"]`),
+/// by method name and then by parameter name.
+pub type AbiTypeOverrides = HashMap<String, HashMap<String, syn::LitStr>>;
+
+/// Returns the name of the parameter with the given pattern.
+pub fn pat_name(pat: &syn::Pat) -> String {
+	match pat {
+		syn::Pat::Ident(pat_ident) => pat_ident.ident.to_string(),
+		other_pat => quote! { #other_pat }.to_string(),
 	}
 }
 
-fn push_canonicalized_vec(target: &mut String, args: &syn::PathArguments) {
-	match args {
-		syn::PathArguments::AngleBracketed(gen_args) => {
-			let last_arg = gen_args.args.last().unwrap();
-			let last_type = last_arg.value();
-			if let syn::GenericArgument::Type(syn::Type::Path(type_path)) = last_type {
-				return if type_path.qself.is_none()
-					&& type_path.path.segments.last().unwrap().value().ident == "u8"
-				{
-					target.push_str("bytes");
-				}
-				else {
-					push_canonicalized_path(target, type_path);
-					target.push_str("[]");
-				}
+/// Strips `#[abi_type = "..."]` attributes from the parameters of the trait
+/// methods in the given tokens, returning the remaining tokens and the overrides.
+///
+/// # Note
+///
+/// Attributes are stripped before parsing since parameter attributes
+/// are not supported by the parser.
+pub fn strip_abi_type_overrides(tokens: TokenStream) -> (TokenStream, AbiTypeOverrides) {
+	let mut overrides = HashMap::new();
+	let tokens = strip_overrides_in(tokens, &mut overrides);
+	(tokens, overrides)
+}
+
+fn strip_overrides_in(tokens: TokenStream, overrides: &mut AbiTypeOverrides) -> TokenStream {
+	let mut after_fn = false;
+	let mut method: Option<String> = None;
+	tokens.into_iter().map(|token| match token {
+		TokenTree::Ident(ident) => {
+			if after_fn {
+				method = Some(ident.to_string());
 			}
-			panic!("Unsupported generic arguments")
+			after_fn = ident == "fn";
+			TokenTree::Ident(ident)
 		},
-		_ => panic!("Unsupported vec arguments"),
-	}
+		TokenTree::Group(group) => {
+			let stream = match (group.delimiter(), method.take()) {
+				(Delimiter::Parenthesis, Some(method)) => strip_param_overrides(group.stream(), method, overrides),
+				(Delimiter::Brace, _) => strip_overrides_in(group.stream(), overrides),
+				_ => group.stream(),
+			};
+			let mut stripped = Group::new(group.delimiter(), stream);
+			stripped.set_span(group.span());
+			TokenTree::Group(stripped)
+		},
+		other => other,
+	}).collect()
 }
 
-fn push_canonicalized_primitive(target: &mut String, seg: &syn::PathSegment) {
-	match seg.ident.to_string().as_str() {
-		"u32" => target.push_str("uint32"),
-		"i32" => target.push_str("int32"),
-		"u64" => target.push_str("uint64"),
-		"i64" => target.push_str("int64"),
-		"U256" => target.push_str("uint256"),
-		"H256" => target.push_str("uint256"),
-		"Address" => target.push_str("address"),
-		"String" => target.push_str("string"),
-		"bool" => target.push_str("bool"),
-		"Vec" => push_canonicalized_vec(target, &seg.arguments),
-		val => panic!(
-			"[e1] Unable to handle param of type {}: not supported by abi",
-			val
-		),
+fn strip_param_overrides(tokens: TokenStream, method: String, overrides: &mut AbiTypeOverrides) -> TokenStream {
+	let mut result = Vec::new();
+	let mut pending: Option<syn::LitStr> = None;
+	let mut tokens = tokens.into_iter().peekable();
+	while let Some(token) = tokens.next() {
+		if let TokenTree::Punct(ref punct) = token {
+			if punct.as_char() == '#' {
+				let abi_type = match tokens.peek() {
+					Some(TokenTree::Group(ref group)) if group.delimiter() == Delimiter::Bracket => {
+						abi_type_attribute(group.stream())
+					},
+					_ => None,
+				};
+				if abi_type.is_some() {
+					pending = abi_type;
+					tokens.next();
+					continue;
+				}
+			}
+		}
+		if let TokenTree::Ident(ref ident) = token {
+			if ident != "mut" {
+				if let Some(abi_type) = pending.take() {
+					overrides
+						.entry(method.clone())
+						.or_insert_with(HashMap::new)
+						.insert(ident.to_string(), abi_type);
+				}
+			}
+		}
+		result.push(token);
 	}
+	result.into_iter().collect()
 }
 
-fn push_canonicalized_path(target: &mut String, type_path: &syn::TypePath) {
-	assert!(type_path.qself.is_none(), "Unsupported type path for canonicalization!");
-	let last_path = type_path.path.segments.last().unwrap();
-	push_canonicalized_primitive(target, *last_path.value())
+/// Returns the abi type of the `abi_type = "..."` attribute contents, if it is one.
+fn abi_type_attribute(tokens: TokenStream) -> Option<syn::LitStr> {
+	match syn::parse2::<syn::MetaNameValue>(tokens) {
+		Ok(ref meta) if meta.ident == "abi_type" => match meta.lit {
+			syn::Lit::Str(ref lit_str) => Some(lit_str.clone()),
+			_ => panic!("abi_type attribute should be a string, e.g. #[abi_type = \"bytes32\"]"),
+		},
+		_ => None,
+	}
 }
 
-fn push_canonicalized_type(target: &mut String, ty: &syn::Type) {
-	match ty {
-		syn::Type::Path(type_path) if type_path.qself.is_none() => {
-			push_canonicalized_path(target, &type_path)
-		},
-		syn::Type::Array(type_array) => {
-			// Special cases for `bytesN`
-			if let syn::Type::Path(type_path) = &*type_array.elem {
-				if "u8" == type_path.path.segments.last().unwrap().value().ident.to_string() {
-					target.push_str("bytes");
-					push_int_const_expr(target, &type_array.len);
-					return;
-				}
+/// Returns the abi type of the parameter of the given type: the overridden one
+/// (`#[abi_type = "..."]`) if any, checked to be canonical, or the described one.
+pub fn resolve_abi_type(ty: &syn::Type, abi_type: Option<&syn::LitStr>) -> Result<ParamType> {
+	match abi_type {
+		Some(abi_type) => {
+			let invalid = |reason| Error::invalid_abi_type(abi_type.value(), reason, abi_type.span());
+			let param_type = ParamType::parse(&abi_type.value()).map_err(&invalid)?;
+			match param_type.canonical_type(&quote! { pwasm_abi }) {
+				Some(_) => Ok(param_type),
+				None => Err(invalid("not implemented by any type".to_owned())),
 			}
-
-			panic!("Unsupported! Use variable-size arrays")
 		},
-		other_type => panic!("[e2] Unable to handle param of type {:?}: not supported by abi", other_type),
+		None => ParamType::describe(ty).ok_or_else(|| {
+			Error::unknown_abi_type(quote! { #ty }.to_string(), syn::spanned::Spanned::span(ty))
+		}),
 	}
 }
 
-/// Returns the canonicalized string representation for the given type.
-pub fn canonicalize_type(ty: &syn::Type) -> String {
-	let mut result = String::new();
-	push_canonicalized_type(&mut result, ty);
-	result
+/// Returns the canonical signature of the function or event with the given name and
+/// parameter types, e.g. `transfer(address,uint256)`.
+pub fn signature(name: &syn::Ident, abi_types: &[ParamType]) -> String {
+	let params: Vec<String> = abi_types.iter().map(|abi_type| abi_type.to_string()).collect();
+	format!("{}({})", name, params.join(","))
 }
 
-/// Returns the canonicalized string representation for the function
-/// with the given name `name` and method signature `method_sig`.
-/// 
+/// Generates the function named `check_ident` which does not compile unless the given types
+/// implement their abi types, i.e. `AbiEncode::Canonical` of each is the canonical type of
+/// its abi type. The errors point to the types.
+///
 /// # Note
-/// 
-/// The result can be used by `function_selector` in order to retrieve
-/// the function selector for the associated function.
-pub fn canonicalize_fn(name: &syn::Ident, method_sig: &syn::MethodSig) -> String {
-	let mut s = String::new();
-	s.push_str(&name.to_string());
-	s.push('(');
-	let total_len = method_sig.decl.inputs.len();
-	for (i, (_, ty)) in iter_signature(method_sig).enumerate() {
-		push_canonicalized_type(&mut s, &ty);
-		if i != total_len-2 { s.push(','); }
+///
+/// The function takes the lifetimes of the method, which the types may refer to.
+pub fn abi_type_checks(
+	krate: &TokenStream,
+	check_ident: &syn::Ident,
+	generics: &syn::Generics,
+	params: &[(&syn::Type, &ParamType)],
+) -> TokenStream {
+	let args: Vec<syn::Ident> = params.iter()
+		.enumerate()
+		.map(|(idx, &(ty, _))| syn::Ident::new(&format!("arg{}", idx), syn::spanned::Spanned::span(ty)))
+		.collect();
+	let arg_decls = params.iter().zip(args.iter()).map(|(&(ty, _), arg)| {
+		quote! { #arg: <#ty as #krate::eth::AbiEncode>::Canonical }
+	});
+	let checks = params.iter().zip(args.iter()).map(|(&(ty, abi_type), arg)| {
+		let canonical = abi_type.canonical_type(krate).expect("Resolved abi types are implemented");
+		let span = syn::spanned::Spanned::span(ty);
+		quote_spanned! { span=> let _: #canonical = #arg; }
+	});
+	quote! {
+		#[allow(dead_code, non_snake_case)]
+		fn #check_ident #generics (#(#arg_decls),*) {
+			#(#checks)*
+		}
 	}
-	s.push(')');
-	s
+}
+
+/// Generates the expression evaluating to the topic (`H256`) of the event with
+/// the given canonical signature, hashed at compile time.
+pub fn topic_toks(krate: &TokenStream, signature: &str) -> TokenStream {
+	let hash_bytes = keccak(signature.as_bytes()).as_ref().iter().map(|b| {
+		syn::Lit::Int(syn::LitInt::new(*b as u64, syn::IntSuffix::U8, Span::call_site()))
+	}).collect::<Vec<_>>();
+	quote! { #krate::types::H256::from([#(#hash_bytes),*]) }
 }
 
 /// Returns the Keccak hash (256-bits) of the given byte slice.
@@ -0,0 +1,17 @@
+//! Canonical types of the abi types (see `AbiEncode::Canonical`)
+//!
+//! Fixed abi types are represented by the rust types implementing them: `uintN`/`intN` by
+//! the integers of the same width (`U256` and `I256` for 256 bits), `address` by `H160`,
+//! `bytesN` by `[u8; N]`, `T[k]` by `[T; k]` and tuples by tuples. The types below stand
+//! for the dynamic abi types, which are implemented by several rust types each.
+
+use lib::PhantomData;
+
+/// Canonical type of `bytes` (e.g. `Vec<u8>` or `&[u8]`)
+pub struct Bytes;
+
+/// Canonical type of `string` (e.g. `String` or `&str`)
+pub struct Str;
+
+/// Canonical type of `T[]` (e.g. `Vec<T>` or `&[T]`), distinct from `Bytes` for `uint8[]`
+pub struct Array<T>(PhantomData<T>);
@@ -1,9 +1,11 @@
 //! Common types encoding/decoding
 
 use lib::*;
-use super::{util, Stream, AbiType, Sink, Error};
-use super::types::{H160, H256, U256};
-use pwasm_std::str::from_utf8;
+use super::{util, canonical, Stream, AbiType, AbiDecode, AbiEncode, FixedAbiType, ArrayItem, Error, ErrorKind};
+#[cfg(feature = "alloc")]
+use super::Sink;
+use super::types::{H160, H256, U256, I256};
+use lib::str::from_utf8;
 
 impl AbiType for u32 {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
@@ -12,7 +14,7 @@ impl AbiType for u32 {
 		let slice = &stream.payload()[previous_position..stream.position()];
 
 		if !slice[..28].iter().all(|x| *x == 0) {
-			return Err(Error::InvalidU32)
+			return Err(ErrorKind::InvalidU32.into())
 		}
 
 		let result = ((slice[28] as u32) << 24) +
@@ -22,12 +24,30 @@ impl AbiType for u32 {
 
 		Ok(result)
 	}
+}
+
+impl AbiEncode for u32 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		sink.write(&util::pad_u32(*self)[..]);
+	}
 
-	fn encode(self, sink: &mut Sink) {
-		sink.preamble_mut().extend_from_slice(&util::pad_u32(self)[..]);
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"uint32".to_owned()
 	}
 
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for u32 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&util::pad_u32(*self)[..]);
+	}
 }
 
 impl AbiType for u64 {
@@ -37,7 +57,7 @@ impl AbiType for u64 {
 		let slice = &stream.payload()[previous_position..stream.position()];
 
 		if !slice[..24].iter().all(|x| *x == 0) {
-			return Err(Error::InvalidU64)
+			return Err(ErrorKind::InvalidU64.into())
 		}
 
 		let result =
@@ -52,63 +72,217 @@ impl AbiType for u64 {
 
 		Ok(result)
 	}
+}
+
+impl AbiEncode for u64 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		sink.write(&util::pad_u64(*self)[..]);
+	}
 
-	fn encode(self, sink: &mut Sink) {
-		sink.preamble_mut().extend_from_slice(&util::pad_u64(self)[..]);
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"uint64".to_owned()
 	}
 
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for u64 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&util::pad_u64(*self)[..]);
+	}
+}
+
+/// Decodes the contents of `bytes` or `string` (length and the padded data)
+fn decode_bytes<'a>(stream: &mut Stream<'a>) -> Result<&'a [u8], Error> {
+	let len = u32::decode(stream)? as usize;
+
+	let previous_position = stream.advance(len)?;
+	stream.allocate(len)?;
+	let result = &stream.payload()[previous_position..stream.position()];
+
+	if stream.is_strict() {
+		let padding_position = stream.advance((32 - len % 32) % 32)?;
+		if !stream.payload()[padding_position..stream.position()].iter().all(|x| *x == 0) {
+			return Err(ErrorKind::InvalidPadding.into());
+		}
+	} else {
+		stream.finish_advance();
+	}
+
+	Ok(result)
+}
+
+/// Encodes the contents of `bytes` or `string` (length and the padded data)
+#[cfg(feature = "alloc")]
+fn encode_bytes(bytes: &[u8], sink: &mut Sink) {
+	sink.push(bytes.len() as u32);
+	sink.write(bytes);
+	sink.write(&[0u8; 32][..(32 - bytes.len() % 32) % 32]);
+}
+
+/// Size of the encoded `bytes` or `string` of the given length
+fn bytes_encoded_size(len: usize) -> usize {
+	32 + (len + 31) / 32 * 32
+}
+
+/// Size of the member of the tuple-like value in the encoding, including
+/// the encoding of the dynamic member itself
+fn member_encoded_size<T: AbiEncode>(member: &T) -> usize {
+	if T::IS_FIXED {
+		T::HEAD_SIZE
+	} else {
+		T::HEAD_SIZE + member.encoded_size()
+	}
+}
+
+impl<'a, T: AbiEncode + ?Sized> AbiEncode for &'a T {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		(**self).encode(sink)
+	}
+
+	fn encoded_size(&self) -> usize {
+		(**self).encoded_size()
+	}
+
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		T::canonical_name()
+	}
+
+	type Canonical = T::Canonical;
+
+	const IS_FIXED: bool = T::IS_FIXED;
+
+	const HEAD_SIZE: usize = T::HEAD_SIZE;
+}
+
+impl AbiEncode for [u8] {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		encode_bytes(self, sink)
+	}
+
+	fn encoded_size(&self) -> usize {
+		bytes_encoded_size(self.len())
+	}
+
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"bytes".to_owned()
+	}
+
+	type Canonical = canonical::Bytes;
+
+	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl AbiEncode for str {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		encode_bytes(self.as_bytes(), sink)
+	}
+
+	fn encoded_size(&self) -> usize {
+		bytes_encoded_size(self.len())
+	}
+
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"string".to_owned()
+	}
+
+	type Canonical = canonical::Str;
+
+	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl<'a, T: AbiType> AbiDecode<'a> for T {
+	fn decode_borrowed(stream: &mut Stream<'a>) -> Result<Self, Error> {
+		T::decode(stream)
+	}
+}
+
+impl<'a> AbiDecode<'a> for &'a [u8] {
+	fn decode_borrowed(stream: &mut Stream<'a>) -> Result<Self, Error> {
+		decode_bytes(stream)
+	}
+}
+
+impl<'a> AbiDecode<'a> for &'a str {
+	fn decode_borrowed(stream: &mut Stream<'a>) -> Result<Self, Error> {
+		Ok(from_utf8(decode_bytes(stream)?).map_err(|_err| ErrorKind::InvalidUtf8)?)
+	}
 }
 
+#[cfg(feature = "alloc")]
 impl AbiType for Vec<u8> {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
-		let len = u32::decode(stream)? as usize;
+		Ok(decode_bytes(stream)?.to_vec())
+	}
+}
 
-		let result = stream.payload()[stream.position()..stream.position() + len].to_vec();
-		stream.advance(len)?;
-		stream.finish_advance();
+#[cfg(feature = "alloc")]
+impl AbiEncode for Vec<u8> {
+	fn encode(&self, sink: &mut Sink) {
+		encode_bytes(self, sink)
+	}
 
-		Ok(result)
+	fn encoded_size(&self) -> usize {
+		bytes_encoded_size(self.len())
 	}
 
-	fn encode(self, sink: &mut Sink) {
-		let mut val = self;
-		let len = val.len();
-		if len % 32 != 0 {
-			val.resize(len + (32 - len % 32), 0);
-		}
-		sink.push(len as u32);
-		sink.preamble_mut().extend_from_slice(&val[..]);
+	fn canonical_name() -> String {
+		"bytes".to_owned()
 	}
 
+	type Canonical = canonical::Bytes;
+
 	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
 }
 
+#[cfg(feature = "alloc")]
 impl AbiType for String {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
-		let len = u32::decode(stream)? as usize;
-
-		let result = from_utf8(&stream.payload()[stream.position()..stream.position() + len])
-			.map_err(|_err| Error::Other)?
+		let result = from_utf8(decode_bytes(stream)?)
+			.map_err(|_err| ErrorKind::InvalidUtf8)?
 			.to_string();
 
-		stream.advance(len)?;
-		stream.finish_advance();
-
 		Ok(result)
 	}
+}
 
-	fn encode(self, sink: &mut Sink) {
-		let mut val = self.into_bytes();
-		let len = val.len();
-		if len % 32 != 0 {
-			val.resize(len + (32 - len % 32), 0);
-		}
-		sink.push(len as u32);
-		sink.preamble_mut().extend_from_slice(&val[..]);
+#[cfg(feature = "alloc")]
+impl AbiEncode for String {
+	fn encode(&self, sink: &mut Sink) {
+		encode_bytes(self.as_bytes(), sink)
 	}
 
+	fn encoded_size(&self) -> usize {
+		bytes_encoded_size(self.len())
+	}
+
+	fn canonical_name() -> String {
+		"string".to_owned()
+	}
+
+	type Canonical = canonical::Str;
+
 	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
 }
 
 impl AbiType for bool {
@@ -117,15 +291,33 @@ impl AbiType for bool {
 		match decoded {
 			0 => Ok(false),
 			1 => Ok(true),
-			_ => Err(Error::InvalidBool),
+			_ => Err(ErrorKind::InvalidBool.into()),
 		}
 	}
+}
+
+impl AbiEncode for bool {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		sink.write(&util::pad_u32(match *self { true => 1, false => 0})[..]);
+	}
 
-	fn encode(self, sink: &mut Sink) {
-		sink.preamble_mut().extend_from_slice(&util::pad_u32(match self { true => 1, false => 0})[..]);
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"bool".to_owned()
 	}
 
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for bool {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&util::pad_u32(match *self { true => 1, false => 0})[..]);
+	}
 }
 
 impl AbiType for U256 {
@@ -136,27 +328,96 @@ impl AbiType for U256 {
 			U256::from_big_endian(&stream.payload()[previous..stream.position()])
 		)
 	}
+}
+
+impl AbiEncode for U256 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		let mut word = [0u8; 32];
+		self.to_big_endian(&mut word);
+		sink.write(&word[..]);
+	}
+
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"uint256".to_owned()
+	}
+
+	type Canonical = Self;
+
+	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for U256 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		self.to_big_endian(target);
+	}
+}
+
+impl AbiType for I256 {
+	fn decode(stream: &mut Stream) -> Result<Self, Error> {
+		Ok(I256::from_raw(U256::decode(stream)?))
+	}
+}
 
-	fn encode(self, sink: &mut Sink) {
-		let tail = sink.preamble_mut().len();
-		sink.preamble_mut().resize(tail + 32, 0);
-		self.to_big_endian(&mut sink.preamble_mut()[tail..tail+32]);
+impl AbiEncode for I256 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		self.into_raw().encode(sink)
 	}
 
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"int256".to_owned()
+	}
+
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for I256 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		self.into_raw().encode_fixed(target)
+	}
 }
 
 impl AbiType for H160 {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
 		let arr = <H256>::decode(stream)?;
+		if stream.is_strict() && !arr[..12].iter().all(|x| *x == 0) {
+			return Err(ErrorKind::InvalidPadding.into());
+		}
 		Ok(H160::from(arr).into())
 	}
+}
 
-	fn encode(self, sink: &mut Sink) {
-		H256::from(self).encode(sink)
+impl AbiEncode for H160 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		H256::from(*self).encode(sink)
 	}
 
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"address".to_owned()
+	}
+
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for H160 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		H256::from(*self).encode_fixed(target)
+	}
 }
 
 impl AbiType for H256 {
@@ -164,39 +425,120 @@ impl AbiType for H256 {
 		let arr = <[u8; 32]>::decode(stream)?;
 		Ok(arr.into())
 	}
+}
 
-	fn encode(self, sink: &mut Sink) {
+impl AbiEncode for H256 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
 		self.as_fixed_bytes().encode(sink)
 	}
 
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"bytes32".to_owned()
+	}
+
+	type Canonical = [u8; 32];
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
 }
 
-impl<T: AbiType> AbiType for Vec<T> {
+impl FixedAbiType for H256 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&self.as_fixed_bytes()[..]);
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ArrayItem> AbiType for Vec<T> {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
 		let len = u32::decode(stream)? as usize;
-		let mut result = Vec::with_capacity(len);
-		for _ in 0..len {
-			result.push(stream.pop()?);
+
+		// offsets of dynamic members are relative to the start of the members, not the length
+		if len > stream.limits().max_elements {
+			return Err(ErrorKind::LimitExceeded.into());
 		}
+		stream.allocate(len.saturating_mul(mem::size_of::<T>()))?;
+
+		let (result, members_stream) = {
+			let mut members_stream = stream.members()?;
+			// every member takes at least one word, so the length can't exceed the payload
+			if len > members_stream.payload().len() / 32 {
+				return Err(ErrorKind::UnexpectedEof.into());
+			}
+			let mut result = Vec::with_capacity(len);
+			for _ in 0..len {
+				result.push(members_stream.pop()?);
+			}
+			(result, members_stream)
+		};
+		let members_len = members_stream.position();
+		stream.join(members_stream)?;
+		stream.advance(members_len)?;
+
 		Ok(result)
 	}
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ArrayItem> AbiEncode for Vec<T> {
+	fn encode(&self, sink: &mut Sink) {
+		self[..].encode(sink)
+	}
+
+	fn encoded_size(&self) -> usize {
+		self[..].encoded_size()
+	}
+
+	fn canonical_name() -> String {
+		let mut result = T::canonical_name();
+		result.push_str("[]");
+		result
+	}
 
-	fn encode(self, sink: &mut Sink) {
+	type Canonical = canonical::Array<T::Canonical>;
+
+	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl<T: ArrayItem> AbiEncode for [T] {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
 		sink.push(self.len() as u32);
+		// offsets of dynamic members are relative to the start of the members, not the length
+		sink.push_members(self.iter());
+	}
 
-		for member in self.into_iter() {
-			sink.push(member);
+	fn encoded_size(&self) -> usize {
+		if T::IS_FIXED {
+			32 + self.len() * T::HEAD_SIZE
+		} else {
+			self.iter().fold(32, |size, member| size + member_encoded_size(member))
 		}
 	}
 
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		let mut result = T::canonical_name();
+		result.push_str("[]");
+		result
+	}
+
+	type Canonical = canonical::Array<T::Canonical>;
+
 	const IS_FIXED: bool = false;
+
+	const HEAD_SIZE: usize = 32;
 }
 
 impl AbiType for i32 {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
 
-		let is_negative = stream.peek() & 0x80 != 0;
+		let is_negative = stream.peek()? & 0x80 != 0;
 
 		if !is_negative {
 			return Ok(u32::decode(stream)? as i32);
@@ -208,7 +550,7 @@ impl AbiType for i32 {
 
 		// only negative path here
 		if !slice[0..28].iter().all(|x| *x == 0xff) {
-			return Err(Error::InvalidPadding);
+			return Err(ErrorKind::InvalidPadding.into());
 		}
 
 		let result = ((slice[28] as u32) << 24) +
@@ -218,18 +560,36 @@ impl AbiType for i32 {
 
 		Ok(result as i32)
 	}
+}
+
+impl AbiEncode for i32 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		sink.write(&util::pad_i32(*self)[..]);
+	}
 
-	fn encode(self, sink: &mut Sink) {
-		sink.preamble_mut().extend_from_slice(&util::pad_i32(self)[..]);
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"int32".to_owned()
 	}
 
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for i32 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&util::pad_i32(*self)[..]);
+	}
 }
 
 impl AbiType for i64 {
 	fn decode(stream: &mut Stream) -> Result<Self, Error> {
 
-		let is_negative = stream.peek() & 0x80 != 0;
+		let is_negative = stream.peek()? & 0x80 != 0;
 
 		if !is_negative {
 			return Ok(u64::decode(stream)? as i64);
@@ -241,7 +601,7 @@ impl AbiType for i64 {
 
 		// only negative path here
 		if !slice[0..24].iter().all(|x| *x == 0xff) {
-			return Err(Error::InvalidPadding);
+			return Err(ErrorKind::InvalidPadding.into());
 		}
 
 		let result =
@@ -256,33 +616,230 @@ impl AbiType for i64 {
 
 		Ok(result as i64)
 	}
+}
+
+impl AbiEncode for i64 {
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink) {
+		sink.write(&util::pad_i64(*self)[..]);
+	}
 
-	fn encode(self, sink: &mut Sink) {
-		sink.preamble_mut().extend_from_slice(&util::pad_i64(self)[..]);
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> String {
+		"int64".to_owned()
 	}
 
+	type Canonical = Self;
+
 	const IS_FIXED: bool = true;
+
+	const HEAD_SIZE: usize = 32;
+}
+
+impl FixedAbiType for i64 {
+	fn encode_fixed(&self, target: &mut [u8]) {
+		target.copy_from_slice(&util::pad_i64(*self)[..]);
+	}
 }
 
+macro_rules! abi_type_uint_impl {
+	($t: ty, $bytes: expr, $err: ident, $name: expr) => {
+		impl AbiType for $t {
+			fn decode(stream: &mut Stream) -> Result<Self, Error> {
+				let previous_position = stream.advance(32)?;
+
+				let slice = &stream.payload()[previous_position..stream.position()];
+
+				if !slice[..32 - $bytes].iter().all(|x| *x == 0) {
+					return Err(ErrorKind::$err.into())
+				}
+
+				let mut bytes = [0u8; $bytes];
+				bytes.copy_from_slice(&slice[32 - $bytes..]);
+
+				Ok(<$t>::from_be_bytes(bytes))
+			}
+		}
+
+		impl AbiEncode for $t {
+			#[cfg(feature = "alloc")]
+			fn encode(&self, sink: &mut Sink) {
+				sink.write(&util::pad_u128(*self as u128)[..]);
+			}
+
+			#[cfg(feature = "alloc")]
+			fn canonical_name() -> String {
+				$name.to_owned()
+			}
+
+			type Canonical = Self;
+
+			const IS_FIXED: bool = true;
+
+			const HEAD_SIZE: usize = 32;
+		}
+
+		impl FixedAbiType for $t {
+			fn encode_fixed(&self, target: &mut [u8]) {
+				target.copy_from_slice(&util::pad_u128(*self as u128)[..]);
+			}
+		}
+	}
+}
+
+macro_rules! abi_type_int_impl {
+	($t: ty, $bytes: expr, $name: expr) => {
+		impl AbiType for $t {
+			fn decode(stream: &mut Stream) -> Result<Self, Error> {
+				let previous_position = stream.advance(32)?;
+
+				let slice = &stream.payload()[previous_position..stream.position()];
+
+				// padding should be the sign extension of the value
+				let padding = if slice[32 - $bytes] & 0x80 != 0 { 0xff } else { 0x00 };
+				if !slice[..32 - $bytes].iter().all(|x| *x == padding) {
+					return Err(ErrorKind::InvalidPadding.into())
+				}
+
+				let mut bytes = [0u8; $bytes];
+				bytes.copy_from_slice(&slice[32 - $bytes..]);
+
+				Ok(<$t>::from_be_bytes(bytes))
+			}
+		}
+
+		impl AbiEncode for $t {
+			#[cfg(feature = "alloc")]
+			fn encode(&self, sink: &mut Sink) {
+				sink.write(&util::pad_i128(*self as i128)[..]);
+			}
+
+			#[cfg(feature = "alloc")]
+			fn canonical_name() -> String {
+				$name.to_owned()
+			}
+
+			type Canonical = Self;
+
+			const IS_FIXED: bool = true;
+
+			const HEAD_SIZE: usize = 32;
+		}
+
+		impl FixedAbiType for $t {
+			fn encode_fixed(&self, target: &mut [u8]) {
+				target.copy_from_slice(&util::pad_i128(*self as i128)[..]);
+			}
+		}
+	}
+}
+
+abi_type_uint_impl!(u8, 1, InvalidU8, "uint8");
+abi_type_uint_impl!(u16, 2, InvalidU16, "uint16");
+abi_type_uint_impl!(u128, 16, InvalidU128, "uint128");
+abi_type_int_impl!(i8, 1, "int8");
+abi_type_int_impl!(i16, 2, "int16");
+abi_type_int_impl!(i128, 16, "int128");
+
 macro_rules! abi_type_fixed_impl {
 	($num: expr) => {
 		impl AbiType for [u8; $num] {
 			fn decode(stream: &mut Stream) -> Result<Self, Error> {
 				let previous_position = stream.advance(32)?;
 				let slice = &stream.payload()[previous_position..stream.position()];
+				if stream.is_strict() && !slice[$num..].iter().all(|x| *x == 0) {
+					return Err(ErrorKind::InvalidPadding.into());
+				}
 				let mut result = [0u8; $num];
 				result.copy_from_slice(&slice[0..$num]);
 				Ok(result)
 			}
+		}
 
-			fn encode(self, sink: &mut Sink) {
+		impl AbiEncode for [u8; $num] {
+			#[cfg(feature = "alloc")]
+			fn encode(&self, sink: &mut Sink) {
 				let mut padded = [0u8; 32];
 				padded[0..$num].copy_from_slice(&self[..]);
-				sink.preamble_mut().extend_from_slice(&padded[..]);
+				sink.write(&padded[..]);
 			}
 
+			#[cfg(feature = "alloc")]
+			fn canonical_name() -> String {
+				concat!("bytes", stringify!($num)).to_owned()
+			}
+
+			type Canonical = Self;
+
 			const IS_FIXED: bool = true;
+
+			const HEAD_SIZE: usize = 32;
 		}
+
+		impl FixedAbiType for [u8; $num] {
+			fn encode_fixed(&self, target: &mut [u8]) {
+				target[0..$num].copy_from_slice(&self[..]);
+				for byte in target[$num..].iter_mut() {
+					*byte = 0;
+				}
+			}
+		}
+
+		impl ArrayItem for [u8; $num] { }
+	}
+}
+
+macro_rules! abi_type_array_impl {
+	($num: expr, $($item: ident)+) => {
+		impl<T: ArrayItem> AbiType for [T; $num] {
+			fn decode(stream: &mut Stream) -> Result<Self, Error> {
+				$(let $item = stream.pop::<T>()?;)+
+				Ok([$($item),+])
+			}
+		}
+
+		impl<T: ArrayItem> AbiEncode for [T; $num] {
+			#[cfg(feature = "alloc")]
+			fn encode(&self, sink: &mut Sink) {
+				sink.reserve_head($num * T::HEAD_SIZE);
+				for item in self.iter() {
+					sink.push(item);
+				}
+			}
+
+			fn encoded_size(&self) -> usize {
+				self.iter().fold(0, |size, item| size + member_encoded_size(item))
+			}
+
+			#[cfg(feature = "alloc")]
+			fn canonical_name() -> String {
+				let mut result = T::canonical_name();
+				result.push_str(concat!("[", stringify!($num), "]"));
+				result
+			}
+
+			type Canonical = [T::Canonical; $num];
+
+			const IS_FIXED: bool = T::IS_FIXED;
+
+			// offset of the dynamic array or the heads of the items of the fixed one
+			const HEAD_SIZE: usize = (T::IS_FIXED as usize) * $num * T::HEAD_SIZE + (!T::IS_FIXED as usize) * 32;
+		}
+
+		impl<T: FixedAbiType + ArrayItem> FixedAbiType for [T; $num] {
+			fn encode_fixed(&self, target: &mut [u8]) {
+				for (index, item) in self.iter().enumerate() {
+					item.encode_fixed(&mut target[index * T::HEAD_SIZE..(index + 1) * T::HEAD_SIZE]);
+				}
+			}
+
+			fn decode_fixed(stream: &mut Stream) -> Result<Self, Error> {
+				$(let $item = stream.pop_fixed::<T>()?;)+
+				Ok([$($item),+])
+			}
+		}
+
+		impl<T: ArrayItem> ArrayItem for [T; $num] { }
 	}
 }
 
@@ -294,17 +851,55 @@ macro_rules! tuple_impls {
 	)+) => {
 		$(
 			impl<$($T:AbiType),+> AbiType for ($($T,)+) {
-				fn decode(_stream: &mut Stream) -> Result<Self, Error> {
-					panic!("Tuples allow only encoding, not decoding (for supporting multiple return types)")
+				fn decode(stream: &mut Stream) -> Result<Self, Error> {
+					Ok(($(stream.pop::<$T>()?,)+))
+				}
+			}
+
+			impl<$($T:AbiEncode),+> AbiEncode for ($($T,)+) {
+				#[cfg(feature = "alloc")]
+				fn encode(&self, sink: &mut Sink) {
+					sink.reserve_head(0 $(+ $T::HEAD_SIZE)+);
+					$(sink.push(&self.$idx);)+
+				}
+
+				fn encoded_size(&self) -> usize {
+					0 $(+ member_encoded_size(&self.$idx))+
 				}
 
-				fn encode(self, sink: &mut Sink) {
-					$(sink.push(self.$idx);)+
+				#[cfg(feature = "alloc")]
+				fn canonical_name() -> String {
+					let members: &[String] = &[$($T::canonical_name()),+];
+					let mut result = "(".to_owned();
+					result.push_str(&members.join(","));
+					result.push(')');
+					result
 				}
 
-				const IS_FIXED: bool = true;
+				type Canonical = ($($T::Canonical,)+);
 
+				const IS_FIXED: bool = $($T::IS_FIXED)&&+;
+
+				// offset of the dynamic tuple or the heads of the members of the fixed one
+				const HEAD_SIZE: usize = (Self::IS_FIXED as usize) * (0 $(+ $T::HEAD_SIZE)+) + (!Self::IS_FIXED as usize) * 32;
+			}
+
+			impl<$($T:FixedAbiType),+> FixedAbiType for ($($T,)+) {
+				#[allow(unused_assignments)]
+				fn encode_fixed(&self, target: &mut [u8]) {
+					let mut position = 0;
+					$(
+						self.$idx.encode_fixed(&mut target[position..position + $T::HEAD_SIZE]);
+						position += $T::HEAD_SIZE;
+					)+
+				}
+
+				fn decode_fixed(stream: &mut Stream) -> Result<Self, Error> {
+					Ok(($(stream.pop_fixed::<$T>()?,)+))
+				}
 			}
+
+			impl<$($T:AbiType),+> ArrayItem for ($($T,)+) { }
 		)+
 	}
 }
@@ -414,6 +1009,26 @@ tuple_impls! {
 	}
 }
 
+impl ArrayItem for u16 { }
+impl ArrayItem for u32 { }
+impl ArrayItem for u64 { }
+impl ArrayItem for u128 { }
+impl ArrayItem for i8 { }
+impl ArrayItem for i16 { }
+impl ArrayItem for i32 { }
+impl ArrayItem for i64 { }
+impl ArrayItem for i128 { }
+impl ArrayItem for bool { }
+impl ArrayItem for U256 { }
+impl ArrayItem for I256 { }
+impl ArrayItem for H160 { }
+impl ArrayItem for H256 { }
+#[cfg(feature = "alloc")]
+impl ArrayItem for String { }
+#[cfg(feature = "alloc")]
+impl ArrayItem for Vec<u8> { }
+#[cfg(feature = "alloc")]
+impl<T: ArrayItem> ArrayItem for Vec<T> { }
 
 abi_type_fixed_impl!(1);
 abi_type_fixed_impl!(2);
@@ -448,6 +1063,39 @@ abi_type_fixed_impl!(30);
 abi_type_fixed_impl!(31);
 abi_type_fixed_impl!(32);
 
+abi_type_array_impl!(1, a0);
+abi_type_array_impl!(2, a0 a1);
+abi_type_array_impl!(3, a0 a1 a2);
+abi_type_array_impl!(4, a0 a1 a2 a3);
+abi_type_array_impl!(5, a0 a1 a2 a3 a4);
+abi_type_array_impl!(6, a0 a1 a2 a3 a4 a5);
+abi_type_array_impl!(7, a0 a1 a2 a3 a4 a5 a6);
+abi_type_array_impl!(8, a0 a1 a2 a3 a4 a5 a6 a7);
+abi_type_array_impl!(9, a0 a1 a2 a3 a4 a5 a6 a7 a8);
+abi_type_array_impl!(10, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9);
+abi_type_array_impl!(11, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10);
+abi_type_array_impl!(12, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11);
+abi_type_array_impl!(13, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12);
+abi_type_array_impl!(14, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13);
+abi_type_array_impl!(15, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14);
+abi_type_array_impl!(16, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15);
+abi_type_array_impl!(17, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16);
+abi_type_array_impl!(18, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17);
+abi_type_array_impl!(19, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18);
+abi_type_array_impl!(20, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19);
+abi_type_array_impl!(21, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20);
+abi_type_array_impl!(22, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21);
+abi_type_array_impl!(23, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22);
+abi_type_array_impl!(24, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23);
+abi_type_array_impl!(25, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24);
+abi_type_array_impl!(26, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25);
+abi_type_array_impl!(27, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26);
+abi_type_array_impl!(28, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27);
+abi_type_array_impl!(29, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27 a28);
+abi_type_array_impl!(30, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27 a28 a29);
+abi_type_array_impl!(31, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27 a28 a29 a30);
+abi_type_array_impl!(32, a0 a1 a2 a3 a4 a5 a6 a7 a8 a9 a10 a11 a12 a13 a14 a15 a16 a17 a18 a19 a20 a21 a22 a23 a24 a25 a26 a27 a28 a29 a30 a31);
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,176 @@
+//! Error module
+
+use lib::*;
+
+/// Kind of the error for decoding rust types from stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+	/// Invalid bool for provided input
+	InvalidBool,
+	/// Invalid u8 for provided input
+	InvalidU8,
+	/// Invalid u16 for provided input
+	InvalidU16,
+	/// Invalid u32 for provided input
+	InvalidU32,
+	/// Invalid u64 for provided input
+	InvalidU64,
+	/// Invalid u128 for provided input
+	InvalidU128,
+	/// Invalid utf-8 for provided string
+	InvalidUtf8,
+	/// Unexpected end of the stream
+	UnexpectedEof,
+	/// Invalid padding for fixed type
+	InvalidPadding,
+	/// Invalid enum discriminant for provided input
+	InvalidEnumValue,
+	/// Decoding limit of the stream exceeded (see `Limits`)
+	LimitExceeded,
+	/// Non-canonical offset of the dynamic value (strict mode)
+	InvalidOffset,
+	/// Unexpected bytes after the encoded values (strict mode)
+	TrailingBytes,
+	/// First topic of the event log is not the hash of the expected event signature
+	InvalidEventSignature,
+	/// Number of topics of the event log does not match the indexed arguments
+	InvalidTopicCount,
+	/// Other error
+	Other,
+}
+
+#[cfg(feature = "std")]
+impl ErrorKind {
+	fn description(&self) -> &'static str {
+		match *self {
+			ErrorKind::InvalidBool => "invalid bool",
+			ErrorKind::InvalidU8 => "invalid u8",
+			ErrorKind::InvalidU16 => "invalid u16",
+			ErrorKind::InvalidU32 => "invalid u32",
+			ErrorKind::InvalidU64 => "invalid u64",
+			ErrorKind::InvalidU128 => "invalid u128",
+			ErrorKind::InvalidUtf8 => "invalid utf-8",
+			ErrorKind::UnexpectedEof => "unexpected end of the stream",
+			ErrorKind::InvalidPadding => "invalid padding",
+			ErrorKind::InvalidEnumValue => "invalid enum discriminant",
+			ErrorKind::LimitExceeded => "decoding limit exceeded",
+			ErrorKind::InvalidOffset => "non-canonical offset",
+			ErrorKind::TrailingBytes => "trailing bytes",
+			ErrorKind::InvalidEventSignature => "invalid event signature",
+			ErrorKind::InvalidTopicCount => "invalid number of topics",
+			ErrorKind::Other => "other error",
+		}
+	}
+}
+
+/// Error for decoding rust types from stream
+///
+/// Along with the kind carries the context of the failure, when known: position
+/// in the payload, the expected abi type (with the `alloc` feature) and the method
+/// and argument being decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+	kind: ErrorKind,
+	position: Option<usize>,
+	#[cfg(feature = "alloc")]
+	expected: Option<String>,
+	method: Option<&'static str>,
+	argument: Option<&'static str>,
+}
+
+impl Error {
+	/// New error of the given kind without context
+	pub fn new(kind: ErrorKind) -> Self {
+		Error {
+			kind: kind,
+			position: None,
+			#[cfg(feature = "alloc")]
+			expected: None,
+			method: None,
+			argument: None,
+		}
+	}
+
+	/// Kind of the error
+	pub fn kind(&self) -> ErrorKind { self.kind }
+
+	/// Position in the payload of the value which failed to decode
+	pub fn position(&self) -> Option<usize> { self.position }
+
+	/// Canonical abi type of the value which failed to decode
+	#[cfg(feature = "alloc")]
+	pub fn expected(&self) -> Option<&str> { self.expected.as_ref().map(|expected| expected.as_str()) }
+
+	/// Name of the method being decoded
+	pub fn method(&self) -> Option<&'static str> { self.method }
+
+	/// Name of the argument being decoded
+	pub fn argument(&self) -> Option<&'static str> { self.argument }
+
+	/// Error with the position and the expected abi type of the value, unless already known
+	///
+	/// Context of the innermost value is preserved, since it is the most precise.
+	#[cfg(feature = "alloc")]
+	pub fn at(mut self, position: usize, expected: String) -> Self {
+		if self.position.is_none() {
+			self.position = Some(position);
+			self.expected = Some(expected);
+		}
+		self
+	}
+
+	/// Error with the position of the value, unless already known
+	pub fn at_position(mut self, position: usize) -> Self {
+		if self.position.is_none() {
+			self.position = Some(position);
+		}
+		self
+	}
+
+	/// Error with the method and argument being decoded
+	pub fn in_argument(mut self, method: &'static str, argument: &'static str) -> Self {
+		self.method = Some(method);
+		self.argument = Some(argument);
+		self
+	}
+}
+
+/// Error of the sink finalization: pushed values take different number of bytes
+/// in the preamble (head) than declared
+#[derive(Debug, PartialEq, Eq)]
+pub struct CapacityMismatch {
+	/// Declared size of the preamble in bytes
+	pub declared: usize,
+	/// Actual size of the preamble in bytes
+	pub pushed: usize,
+}
+
+impl From<ErrorKind> for Error {
+	fn from(kind: ErrorKind) -> Self {
+		Error::new(kind)
+	}
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.kind.description())?;
+		if let Some(ref expected) = self.expected {
+			write!(f, " decoding {}", expected)?;
+		}
+		if let Some(position) = self.position {
+			write!(f, " at byte {}", position)?;
+		}
+		if let (Some(method), Some(argument)) = (self.method, self.argument) {
+			write!(f, " (argument `{}` of `{}`)", argument, method)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for Error {
+	fn description(&self) -> &str {
+		self.kind.description()
+	}
+}
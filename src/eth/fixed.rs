@@ -0,0 +1,44 @@
+//! Fixed sink module
+
+use super::{FixedAbiType, CapacityMismatch};
+
+/// Sink writing fixed-size values into the borrowed buffer (e.g. `[u8; 64]` for two
+/// words) without allocation
+pub struct FixedSink<'a> {
+	target: &'a mut [u8],
+	position: usize,
+}
+
+impl<'a> FixedSink<'a> {
+	/// New sink writing to the given buffer
+	pub fn new(target: &'a mut [u8]) -> Self {
+		FixedSink {
+			target: target,
+			position: 0,
+		}
+	}
+
+	/// Push `val` to the sink. Fails if it does not fit into the rest of the buffer.
+	pub fn push<T: FixedAbiType>(&mut self, val: &T) -> Result<(), CapacityMismatch> {
+		let end = self.position + T::HEAD_SIZE;
+		if end > self.target.len() {
+			return Err(CapacityMismatch { declared: self.target.len(), pushed: end });
+		}
+		val.encode_fixed(&mut self.target[self.position..end]);
+		self.position = end;
+		Ok(())
+	}
+
+	/// Number of bytes written
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	/// Finish writing. Fails if the buffer is not filled by the pushed values.
+	pub fn finalize(self) -> Result<(), CapacityMismatch> {
+		if self.position != self.target.len() {
+			return Err(CapacityMismatch { declared: self.target.len(), pushed: self.position });
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,26 @@
+//! Hashing module
+
+use byteorder::{BigEndian, ByteOrder};
+use tiny_keccak::Keccak;
+use super::types::H256;
+
+/// Keccak-256 hash of the given bytes
+pub fn keccak256(bytes: &[u8]) -> H256 {
+	let mut keccak = Keccak::new_keccak256();
+	let mut result = H256::zero();
+	keccak.update(bytes);
+	keccak.finalize(result.as_mut());
+	result
+}
+
+/// Function selector (first 4 bytes of the keccak256 hash, big-endian) for the given
+/// canonical function signature, e.g. `selector("transfer(address,uint256)") == 0xa9059cbb`
+pub fn selector(signature: &str) -> u32 {
+	BigEndian::read_u32(&keccak256(signature.as_bytes())[0..4])
+}
+
+/// Event topic (topic0 of the log) for the given canonical event signature,
+/// e.g. `event_topic("Transfer(address,address,uint256)")`
+pub fn event_topic(signature: &str) -> H256 {
+	keccak256(signature.as_bytes())
+}
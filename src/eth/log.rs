@@ -1,13 +1,70 @@
 //! Log module
 
+use lib::*;
 use byteorder::{BigEndian, ByteOrder};
 use super::types::*;
+use super::{util, keccak256, Stream, AbiDecode, ArrayItem, Error, ErrorKind};
 
 /// As log trait for how primitive types are represented as indexed arguments
 /// of the event log
+///
+/// Value types are represented by their abi encoding, while `bytes`, `string`, arrays
+/// and structs are represented by the keccak256 hash of their in-place encoding
+/// (see `encode_indexed`), as in solidity.
 pub trait AsLog {
 	/// Convert type to hash representation for the event log.
 	fn as_log(&self) -> H256;
+
+	/// Append the in-place encoding of the value as a member of the indexed array or struct:
+	/// the items of the arrays and the fields of the structs are concatenated without offsets
+	/// and lengths, and `bytes` and `string` are padded to a multiple of 32 bytes.
+	///
+	/// Defaults to the representation of the value types.
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		target.extend_from_slice(self.as_log().as_bytes());
+	}
+}
+
+/// Appends bytes padded to a multiple of 32 bytes
+fn encode_indexed_bytes(bytes: &[u8], target: &mut Vec<u8>) {
+	target.extend_from_slice(bytes);
+	let padding = (32 - bytes.len() % 32) % 32;
+	target.extend_from_slice(&[0u8; 32][..padding]);
+}
+
+/// Hash of the in-place encoding of the items of the array
+fn items_as_log<T: AsLog>(items: &[T]) -> H256 {
+	let mut encoded = Vec::with_capacity(32 * items.len());
+	for item in items {
+		item.encode_indexed(&mut encoded);
+	}
+	keccak256(&encoded)
+}
+
+impl<'a, T: AsLog + ?Sized> AsLog for &'a T {
+	fn as_log(&self) -> H256 {
+		(**self).as_log()
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		(**self).encode_indexed(target)
+	}
+}
+
+impl AsLog for u8 {
+	fn as_log(&self) -> H256 {
+		let mut result = H256::zero();
+		result.as_mut()[31] = *self;
+		result
+	}
+}
+
+impl AsLog for u16 {
+	fn as_log(&self) -> H256 {
+		let mut result = H256::zero();
+		BigEndian::write_u16(&mut result.as_mut()[30..32], *self);
+		result
+	}
 }
 
 impl AsLog for u32 {
@@ -26,27 +83,48 @@ impl AsLog for u64 {
 	}
 }
 
-impl AsLog for i64 {
+impl AsLog for u128 {
 	fn as_log(&self) -> H256 {
 		let mut result = H256::zero();
-		BigEndian::write_i64(&mut result.as_mut()[24..32], *self);
+		result.as_mut()[16..32].copy_from_slice(&self.to_be_bytes());
 		result
 	}
 }
 
-impl AsLog for i32 {
+impl AsLog for i8 {
 	fn as_log(&self) -> H256 {
-		let mut result = H256::zero();
-		BigEndian::write_i32(&mut result.as_mut()[28..32], *self);
-		result
+		util::pad_i128(*self as i128).into()
 	}
 }
 
+impl AsLog for i16 {
+	fn as_log(&self) -> H256 {
+		util::pad_i128(*self as i128).into()
+	}
+}
+
+impl AsLog for i128 {
+	fn as_log(&self) -> H256 {
+		util::pad_i128(*self).into()
+	}
+}
+
+impl AsLog for i64 {
+	fn as_log(&self) -> H256 {
+		util::pad_i64(*self).into()
+	}
+}
+
+impl AsLog for i32 {
+	fn as_log(&self) -> H256 {
+		util::pad_i32(*self).into()
+	}
+}
 
 impl AsLog for bool {
 	fn as_log(&self) -> H256 {
 		let mut result = H256::zero();
-		result.as_mut()[32] = if *self { 1 } else { 0 };
+		result.as_mut()[31] = if *self { 1 } else { 0 };
 		result
 	}
 }
@@ -59,6 +137,12 @@ impl AsLog for U256 {
 	}
 }
 
+impl AsLog for I256 {
+	fn as_log(&self) -> H256 {
+		self.into_raw().as_log()
+	}
+}
+
 impl AsLog for H256 {
 	fn as_log(&self) -> H256 {
 		self.clone()
@@ -70,3 +154,284 @@ impl AsLog for Address {
 		(*self).into()
 	}
 }
+
+impl AsLog for [u8] {
+	fn as_log(&self) -> H256 {
+		keccak256(self)
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		encode_indexed_bytes(self, target)
+	}
+}
+
+impl AsLog for Vec<u8> {
+	fn as_log(&self) -> H256 {
+		self[..].as_log()
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		self[..].encode_indexed(target)
+	}
+}
+
+impl AsLog for str {
+	fn as_log(&self) -> H256 {
+		self.as_bytes().as_log()
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		self.as_bytes().encode_indexed(target)
+	}
+}
+
+impl AsLog for String {
+	fn as_log(&self) -> H256 {
+		self.as_bytes().as_log()
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		self.as_bytes().encode_indexed(target)
+	}
+}
+
+impl<T: AsLog + ArrayItem> AsLog for [T] {
+	fn as_log(&self) -> H256 {
+		items_as_log(self)
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		for item in self.iter() {
+			item.encode_indexed(target);
+		}
+	}
+}
+
+impl<T: AsLog + ArrayItem> AsLog for Vec<T> {
+	fn as_log(&self) -> H256 {
+		items_as_log(self)
+	}
+
+	fn encode_indexed(&self, target: &mut Vec<u8>) {
+		self[..].encode_indexed(target)
+	}
+}
+
+macro_rules! as_log_fixed_impl {
+	($($num: expr),+) => {
+		$(
+			impl AsLog for [u8; $num] {
+				fn as_log(&self) -> H256 {
+					// bytesN is the value type, left aligned
+					let mut result = H256::zero();
+					result.as_mut()[..$num].copy_from_slice(&self[..]);
+					result
+				}
+			}
+
+			impl FromLog for [u8; $num] {
+				fn from_log(topic: &H256) -> Result<Self, Error> {
+					Stream::new(topic.as_bytes()).pop()
+				}
+			}
+
+			impl<T: AsLog + ArrayItem> AsLog for [T; $num] {
+				fn as_log(&self) -> H256 {
+					items_as_log(self)
+				}
+
+				fn encode_indexed(&self, target: &mut Vec<u8>) {
+					self[..].encode_indexed(target)
+				}
+			}
+		)+
+	}
+}
+
+as_log_fixed_impl!(
+	1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+	17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32
+);
+
+macro_rules! as_log_tuple_impls {
+	($(($($idx:tt -> $T:ident)+))+) => {
+		$(
+			impl<$($T:AsLog),+> AsLog for ($($T,)+) {
+				fn as_log(&self) -> H256 {
+					let mut encoded = Vec::new();
+					self.encode_indexed(&mut encoded);
+					keccak256(&encoded)
+				}
+
+				fn encode_indexed(&self, target: &mut Vec<u8>) {
+					$(self.$idx.encode_indexed(target);)+
+				}
+			}
+		)+
+	}
+}
+
+as_log_tuple_impls! {
+	(0 -> A)
+	(0 -> A 1 -> B)
+	(0 -> A 1 -> B 2 -> C)
+	(0 -> A 1 -> B 2 -> C 3 -> D)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J 10 -> K)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J 10 -> K 11 -> L)
+}
+
+/// Inverse of `AsLog`: how primitive types are decoded from the indexed arguments
+/// of the event log
+pub trait FromLog : Sized {
+	/// Instantiate type from the hash representation in the event log.
+	fn from_log(topic: &H256) -> Result<Self, Error>;
+}
+
+macro_rules! from_log_impl {
+	($($t: ty),+) => {
+		$(
+			impl FromLog for $t {
+				fn from_log(topic: &H256) -> Result<Self, Error> {
+					// topics of the value types are the same as their abi encoding
+					Stream::new(topic.as_bytes()).pop()
+				}
+			}
+		)+
+	}
+}
+
+from_log_impl!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool, U256, I256, H256, Address);
+
+/// Stream for decoding the event log (the topics and the data) back to typed values
+///
+/// The first topic is checked to be the hash of the event signature, then the indexed
+/// arguments are popped from the rest of the topics (`pop_indexed`) and the non-indexed
+/// ones from the data (`pop`), in the order of their declaration.
+pub struct LogStream<'a> {
+	topics: &'a [H256],
+	data: Stream<'a>,
+}
+
+impl<'a> LogStream<'a> {
+	/// New stream for the log of the event with the given signature hash (topic0).
+	/// Fails if the first topic does not match it.
+	pub fn new(signature: &H256, topics: &'a [H256], data: &'a [u8]) -> Result<Self, Error> {
+		match topics.split_first() {
+			Some((topic, rest)) if topic == signature => Ok(LogStream {
+				topics: rest,
+				data: Stream::new(data),
+			}),
+			_ => Err(ErrorKind::InvalidEventSignature.into()),
+		}
+	}
+
+	/// Pop next indexed argument from the topics
+	pub fn pop_indexed<T: FromLog>(&mut self) -> Result<T, Error> {
+		let (topic, rest) = self.topics.split_first().ok_or(Error::new(ErrorKind::InvalidTopicCount))?;
+		self.topics = rest;
+		T::from_log(topic)
+	}
+
+	/// Pop next non-indexed argument from the data
+	pub fn pop<T: AbiDecode<'a>>(&mut self) -> Result<T, Error> {
+		self.data.pop()
+	}
+
+	/// Finish decoding, checks that all topics are consumed
+	/// (and that there are no trailing bytes in the data in the strict mode, see `data_mut`)
+	pub fn finish(&self) -> Result<(), Error> {
+		if !self.topics.is_empty() {
+			return Err(ErrorKind::InvalidTopicCount.into());
+		}
+		self.data.finish()
+	}
+
+	/// Stream of the non-indexed arguments, e.g. to change its mode
+	pub fn data_mut(&mut self) -> &mut Stream<'a> {
+		&mut self.data
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use lib::*;
+	use super::{AsLog, FromLog};
+	use super::super::types::*;
+
+	/// Word filled with `fill`, ending with `tail`
+	fn word(fill: u8, tail: &[u8]) -> H256 {
+		let mut result = [fill; 32];
+		result[32 - tail.len()..].copy_from_slice(tail);
+		result.into()
+	}
+
+	/// Checks the topic of the value and that it is decoded back
+	fn check<T: AsLog + FromLog + PartialEq + Debug>(value: T, topic: H256) {
+		assert_eq!(value.as_log(), topic);
+		assert_eq!(T::from_log(&topic), Ok(value));
+	}
+
+	#[test]
+	fn bool() {
+		check(false, word(0, &[]));
+		check(true, word(0, &[1]));
+	}
+
+	#[test]
+	fn unsigned() {
+		check(0u8, word(0, &[]));
+		check(u8::max_value(), word(0, &[0xff]));
+		check(u16::max_value(), word(0, &[0xff; 2]));
+		check(0x0102u16, word(0, &[0x01, 0x02]));
+		check(u32::max_value(), word(0, &[0xff; 4]));
+		check(0x01020304u32, word(0, &[0x01, 0x02, 0x03, 0x04]));
+		check(u64::max_value(), word(0, &[0xff; 8]));
+		check(u128::max_value(), word(0, &[0xff; 16]));
+		check(U256::zero(), word(0, &[]));
+		check(U256::max_value(), word(0xff, &[]));
+	}
+
+	#[test]
+	fn signed() {
+		check(0i8, word(0, &[]));
+		check(-1i8, word(0xff, &[]));
+		check(i8::min_value(), word(0xff, &[0x80]));
+		check(i8::max_value(), word(0, &[0x7f]));
+		check(i16::min_value(), word(0xff, &[0x80, 0x00]));
+		check(i16::max_value(), word(0, &[0x7f, 0xff]));
+		check(-1i32, word(0xff, &[]));
+		check(-2i32, word(0xff, &[0xfe]));
+		check(i32::min_value(), word(0xff, &[0x80, 0, 0, 0]));
+		check(i32::max_value(), word(0, &[0x7f, 0xff, 0xff, 0xff]));
+		check(-1i64, word(0xff, &[]));
+		check(i64::min_value(), word(0xff, &[0x80, 0, 0, 0, 0, 0, 0, 0]));
+		check(i64::max_value(), word(0, &[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]));
+		check(-1i128, word(0xff, &[]));
+		check(i128::min_value(), word(0xff, &[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]));
+		check(I256::from(-1i64), word(0xff, &[]));
+		check(I256::min_value(), {
+			let mut min = [0u8; 32];
+			min[0] = 0x80;
+			H256::from(min)
+		});
+		check(I256::max_value(), {
+			let mut max = [0xffu8; 32];
+			max[0] = 0x7f;
+			H256::from(max)
+		});
+	}
+
+	#[test]
+	fn hashes() {
+		check(H256::zero(), word(0, &[]));
+		check(H256::from([0xabu8; 32]), word(0xab, &[]));
+		check(Address::from([0x11u8; 20]), word(0, &[0x11; 20]));
+		check(Address::from([0xffu8; 20]), word(0, &[0xff; 20]));
+	}
+}
@@ -3,51 +3,130 @@
 #![warn(missing_docs)]
 
 mod util;
+pub mod canonical;
+mod error;
+mod hash;
+#[cfg(feature = "alloc")]
 mod log;
 mod stream;
+#[cfg(feature = "alloc")]
 mod sink;
+mod fixed;
+#[cfg(feature = "alloc")]
+mod packed;
 mod common;
 #[cfg(test)]
 mod tests;
 
-pub use self::log::AsLog;
-pub use self::stream::Stream;
+pub use self::error::{Error, ErrorKind, CapacityMismatch};
+pub use self::hash::{keccak256, selector, event_topic};
+#[cfg(feature = "alloc")]
+pub use self::log::{AsLog, FromLog, LogStream};
+pub use self::stream::{Stream, Limits};
+#[cfg(feature = "alloc")]
 pub use self::sink::Sink;
+pub use self::fixed::FixedSink;
+#[cfg(feature = "alloc")]
+pub use self::packed::PackedSink;
 
 use super::types;
 
-/// Error for decoding rust types from stream
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error {
-	/// Invalid bool for provided input
-	InvalidBool,
-	/// Invalid u32 for provided input
-	InvalidU32,
-	/// Invalid u64 for provided input
-	InvalidU64,
-	/// Unexpected end of the stream
-	UnexpectedEof,
-	/// Invalid padding for fixed type
-	InvalidPadding,
-	/// Other error
-	Other,
-}
-
 /// Abi type trait
-pub trait AbiType : Sized {
+pub trait AbiType : AbiEncode + Sized {
 	/// Insantiate type from data stream
 	/// Should never be called manually! Use stream.pop()
 	fn decode(stream: &mut Stream) -> Result<Self, Error>;
+}
 
+/// Abi type which can be decoded from the payload with the lifetime `'a`
+///
+/// Besides the abi types, implemented for `&'a [u8]` (`bytes`) and `&'a str` (`string`),
+/// which are borrowed from the payload rather than copied.
+pub trait AbiDecode<'a> : AbiEncode + Sized {
+	/// Insantiate type from data stream
+	/// Should never be called manually! Use stream.pop()
+	fn decode_borrowed(stream: &mut Stream<'a>) -> Result<Self, Error>;
+}
+
+/// Encoding part of the abi type
+///
+/// Besides the abi types, implemented for the references to them and for the borrowed
+/// `str`, `[u8]` and `[T]`, so that the values can be pushed to the sink without being
+/// consumed (e.g. `sink.push(&value)` or `sink.push("string")`).
+///
+/// `encode` and `canonical_name` require the `alloc` feature; without it only the layout
+/// of the type is known, which is enough for `FixedAbiType`.
+pub trait AbiEncode {
 	/// Push type to data sink
 	/// Should never be called manually! Use sink.push(val)
-	fn encode(self, sink: &mut Sink);
+	#[cfg(feature = "alloc")]
+	fn encode(&self, sink: &mut Sink);
+
+	/// Canonical name of the type in the abi (e.g. `uint256`, `bytes32[]` or
+	/// `(address,uint256)`), as used in the function and event signatures
+	#[cfg(feature = "alloc")]
+	fn canonical_name() -> ::lib::String;
+
+	/// Rust type standing for the abi type, the same for all the types with the same
+	/// `canonical_name` (e.g. `canonical::Bytes` for `Vec<u8>` and `&[u8]`, `[u8; 32]` for `H256`)
+	///
+	/// Lets `eth_abi` check the abi types of the parameters at compile time.
+	type Canonical;
+
+	/// Size of the encoded value in bytes, not including the offset in the head of the enclosing
+	/// tuple for the dynamic types. Equals to `HEAD_SIZE` for the fixed types.
+	///
+	/// Used to allocate the encoding buffer once, so should be exact.
+	fn encoded_size(&self) -> usize {
+		Self::HEAD_SIZE
+	}
 
 	/// Whether type has fixed length or not
 	const IS_FIXED: bool;
+
+	/// Number of bytes the type takes in the head of the enclosing tuple: the size of the
+	/// encoding for the fixed types and the size of the offset (32) for the dynamic ones
+	const HEAD_SIZE: usize;
+}
+
+/// Packed (non-standard) encoding of the abi type, as `abi.encodePacked` in solidity
+///
+/// Used to reproduce the hashes computed by the contracts (e.g. of the signed messages).
+/// Besides the abi types, implemented for the references to them and for the borrowed
+/// `str`, `[u8]` and `[T]`.
+#[cfg(feature = "alloc")]
+pub trait AbiEncodePacked {
+	/// Push type to packed data sink
+	/// Should never be called manually! Use packed_sink.push(val)
+	fn encode_packed(&self, sink: &mut PackedSink);
 }
 
+/// Abi type of the fixed size, which can be encoded into and decoded from the borrowed
+/// buffer without allocation (see `FixedSink` and `Stream::pop_fixed`)
+///
+/// Implemented for the primitive types, `bytesN`, and fixed arrays and tuples of them, as well
+/// as for the types deriving `AbiType` with only such fields. Required for the items of the
+/// arrays in the packed encoding (`AbiEncodePacked`).
+pub trait FixedAbiType : AbiType {
+	/// Encode value into the target, which is exactly `HEAD_SIZE` bytes long
+	/// Should never be called manually! Use fixed_sink.push(&val)
+	fn encode_fixed(&self, target: &mut [u8]);
+
+	/// Insantiate type from data stream, without allocation even on failure
+	/// Should never be called manually! Use stream.pop_fixed()
+	fn decode_fixed(stream: &mut Stream) -> Result<Self, Error> {
+		Self::decode(stream)
+	}
+}
+
+/// Abi type which can be used as an element of the variable-size array (`Vec<T>`)
+///
+/// Implemented for every abi type except `u8`, since `Vec<u8>` is encoded as `bytes`
+/// rather than `uint8[]`.
+pub trait ArrayItem : AbiType { }
+
 /// Endpoint interface for contracts
+#[cfg(feature = "alloc")]
 pub trait EndpointInterface {
 	/// Dispatch payload for regular method
 	fn dispatch(&mut self, payload: &[u8]) -> ::lib::Vec<u8>;
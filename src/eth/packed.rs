@@ -0,0 +1,261 @@
+//! Packed (non-standard) encoding module
+
+use lib::*;
+use super::{AbiEncodePacked, FixedAbiType, ArrayItem};
+use super::types::{H160, H256, U256, I256};
+
+/// Sink for the packed (non-standard) encoding, equivalent to `abi.encodePacked` in solidity
+///
+/// Values are concatenated without offsets and lengths: the fixed types take the minimal
+/// number of bytes (e.g. 2 for `uint16`, 20 for `address`), while `bytes` and `string` are
+/// written as is, without padding. The items of the arrays are padded to 32 bytes, as in
+/// the standard encoding, so only the arrays of the fixed types (`FixedAbiType`) can be packed:
+///
+/// ```
+/// # extern crate pwasm_abi;
+/// # use pwasm_abi::eth::PackedSink;
+/// # fn main() {
+/// let mut sink = PackedSink::new();
+/// sink.push(vec![1u32, 2u32]);
+/// assert_eq!(sink.finalize().len(), 64);
+/// # }
+/// ```
+///
+/// ```compile_fail
+/// # extern crate pwasm_abi;
+/// # use pwasm_abi::eth::PackedSink;
+/// # fn main() {
+/// let mut sink = PackedSink::new();
+/// sink.push(vec!["dynamic".to_owned()]);
+/// # }
+/// ```
+pub struct PackedSink {
+	buffer: Vec<u8>,
+}
+
+impl PackedSink {
+	/// New empty sink
+	pub fn new() -> Self {
+		PackedSink { buffer: Vec::new() }
+	}
+
+	/// Push `val` to the sink, either owned or borrowed (e.g. `sink.push(&value)`)
+	pub fn push<T: AbiEncodePacked>(&mut self, val: T) {
+		val.encode_packed(self);
+	}
+
+	/// Write raw bytes to the sink
+	pub fn write(&mut self, data: &[u8]) {
+		self.buffer.extend_from_slice(data);
+	}
+
+	/// Consume current sink to produce a vector with content
+	pub fn finalize(self) -> Vec<u8> {
+		self.buffer
+	}
+}
+
+/// Writes the last `len` bytes of the standard (padded) encoding of the value
+fn write_tail<T: FixedAbiType>(val: &T, len: usize, sink: &mut PackedSink) {
+	let mut word = [0u8; 32];
+	val.encode_fixed(&mut word);
+	sink.write(&word[32 - len..]);
+}
+
+/// Writes the items of the array padded as in the standard encoding, without length
+///
+/// The arrays of the dynamic types have no packed encoding, hence the `FixedAbiType` bound.
+fn write_items<'a, T: FixedAbiType + ArrayItem + 'a, I: ExactSizeIterator<Item=&'a T>>(items: I, sink: &mut PackedSink) {
+	sink.buffer.reserve(items.len() * T::HEAD_SIZE);
+	for item in items {
+		let start = sink.buffer.len();
+		sink.buffer.resize(start + T::HEAD_SIZE, 0);
+		item.encode_fixed(&mut sink.buffer[start..]);
+	}
+}
+
+impl<'a, T: AbiEncodePacked + ?Sized> AbiEncodePacked for &'a T {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		(**self).encode_packed(sink)
+	}
+}
+
+macro_rules! packed_int_impl {
+	($t: ty, $len: expr) => {
+		impl AbiEncodePacked for $t {
+			fn encode_packed(&self, sink: &mut PackedSink) {
+				write_tail(self, $len, sink)
+			}
+		}
+	}
+}
+
+packed_int_impl!(u8, 1);
+packed_int_impl!(u16, 2);
+packed_int_impl!(u32, 4);
+packed_int_impl!(u64, 8);
+packed_int_impl!(u128, 16);
+packed_int_impl!(i8, 1);
+packed_int_impl!(i16, 2);
+packed_int_impl!(i32, 4);
+packed_int_impl!(i64, 8);
+packed_int_impl!(i128, 16);
+packed_int_impl!(bool, 1);
+packed_int_impl!(U256, 32);
+packed_int_impl!(I256, 32);
+packed_int_impl!(H160, 20);
+
+impl AbiEncodePacked for H256 {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		sink.write(self.as_bytes())
+	}
+}
+
+impl AbiEncodePacked for [u8] {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		sink.write(self)
+	}
+}
+
+impl AbiEncodePacked for Vec<u8> {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		sink.write(self)
+	}
+}
+
+impl AbiEncodePacked for str {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		sink.write(self.as_bytes())
+	}
+}
+
+impl AbiEncodePacked for String {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		sink.write(self.as_bytes())
+	}
+}
+
+impl<T: FixedAbiType + ArrayItem> AbiEncodePacked for [T] {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		write_items(self.iter(), sink)
+	}
+}
+
+impl<T: FixedAbiType + ArrayItem> AbiEncodePacked for Vec<T> {
+	fn encode_packed(&self, sink: &mut PackedSink) {
+		write_items(self.iter(), sink)
+	}
+}
+
+macro_rules! packed_fixed_impl {
+	($num: expr) => {
+		impl AbiEncodePacked for [u8; $num] {
+			fn encode_packed(&self, sink: &mut PackedSink) {
+				sink.write(&self[..])
+			}
+		}
+	}
+}
+
+macro_rules! packed_array_impl {
+	($num: expr) => {
+		impl<T: FixedAbiType + ArrayItem> AbiEncodePacked for [T; $num] {
+			fn encode_packed(&self, sink: &mut PackedSink) {
+				write_items(self.iter(), sink)
+			}
+		}
+	}
+}
+
+packed_fixed_impl!(1);
+packed_fixed_impl!(2);
+packed_fixed_impl!(3);
+packed_fixed_impl!(4);
+packed_fixed_impl!(5);
+packed_fixed_impl!(6);
+packed_fixed_impl!(7);
+packed_fixed_impl!(8);
+packed_fixed_impl!(9);
+packed_fixed_impl!(10);
+packed_fixed_impl!(11);
+packed_fixed_impl!(12);
+packed_fixed_impl!(13);
+packed_fixed_impl!(14);
+packed_fixed_impl!(15);
+packed_fixed_impl!(16);
+packed_fixed_impl!(17);
+packed_fixed_impl!(18);
+packed_fixed_impl!(19);
+packed_fixed_impl!(20);
+packed_fixed_impl!(21);
+packed_fixed_impl!(22);
+packed_fixed_impl!(23);
+packed_fixed_impl!(24);
+packed_fixed_impl!(25);
+packed_fixed_impl!(26);
+packed_fixed_impl!(27);
+packed_fixed_impl!(28);
+packed_fixed_impl!(29);
+packed_fixed_impl!(30);
+packed_fixed_impl!(31);
+packed_fixed_impl!(32);
+
+packed_array_impl!(1);
+packed_array_impl!(2);
+packed_array_impl!(3);
+packed_array_impl!(4);
+packed_array_impl!(5);
+packed_array_impl!(6);
+packed_array_impl!(7);
+packed_array_impl!(8);
+packed_array_impl!(9);
+packed_array_impl!(10);
+packed_array_impl!(11);
+packed_array_impl!(12);
+packed_array_impl!(13);
+packed_array_impl!(14);
+packed_array_impl!(15);
+packed_array_impl!(16);
+packed_array_impl!(17);
+packed_array_impl!(18);
+packed_array_impl!(19);
+packed_array_impl!(20);
+packed_array_impl!(21);
+packed_array_impl!(22);
+packed_array_impl!(23);
+packed_array_impl!(24);
+packed_array_impl!(25);
+packed_array_impl!(26);
+packed_array_impl!(27);
+packed_array_impl!(28);
+packed_array_impl!(29);
+packed_array_impl!(30);
+packed_array_impl!(31);
+packed_array_impl!(32);
+
+macro_rules! packed_tuple_impls {
+	($(($($idx:tt -> $T:ident)+))+) => {
+		$(
+			impl<$($T:AbiEncodePacked),+> AbiEncodePacked for ($($T,)+) {
+				fn encode_packed(&self, sink: &mut PackedSink) {
+					$(self.$idx.encode_packed(sink);)+
+				}
+			}
+		)+
+	}
+}
+
+packed_tuple_impls! {
+	(0 -> A)
+	(0 -> A 1 -> B)
+	(0 -> A 1 -> B 2 -> C)
+	(0 -> A 1 -> B 2 -> C 3 -> D)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J 10 -> K)
+	(0 -> A 1 -> B 2 -> C 3 -> D 4 -> E 5 -> F 6 -> G 7 -> H 8 -> I 9 -> J 10 -> K 11 -> L)
+}
@@ -1,69 +1,259 @@
 //! Sink module;
 
 use lib::*;
-use super::AbiType;
+use super::{util, AbiEncode, CapacityMismatch};
 
 /// Sink for returning number of arguments
+///
+/// Values are encoded in place: heads of the tuple being encoded are reserved before its members
+/// are pushed, and the encodings of the dynamic members are appended after them, so that the
+/// output buffer is allocated once when its size is declared (see `Sink::with_size`).
 pub struct Sink {
-	capacity: usize,
+	/// Declared size of the top-level preamble, which is then reserved upfront in the buffer
+	capacity: Option<usize>,
+	/// Top-level heads, if their size is not declared
 	preamble: Vec<u8>,
-	heap: Vec<u8>,
+	/// Offsets of dynamic values, written to the preamble once its final size is known.
+	/// Pairs of (position of the offset in the preamble, position of the value in the buffer).
+	offsets: Vec<(usize, usize)>,
+	buffer: Vec<u8>,
+	/// Start of the value being encoded in the buffer (which offsets are relative to)
+	/// and the write position in its head, or `None` if heads are written to the preamble
+	frame: Option<(usize, usize)>,
+	/// Nesting of the value being encoded
+	depth: usize,
+	/// Whether the buffer is allocated for the whole encoding upfront, otherwise it is
+	/// grown by each top-level dynamic value pushed
+	sized: bool,
+	/// Bytes written through the deprecated `preamble_mut` and `heap_mut`,
+	/// moved to the head and the tail of the value being encoded by the next operation
+	legacy_head: Vec<u8>,
+	legacy_tail: Vec<u8>,
 }
 
 impl Sink {
-	/// New sink with known capacity
+	/// New sink with known capacity (number of words in the preamble)
 	pub fn new(capacity: usize) -> Self {
 		Sink {
-			capacity: 32 * capacity,
-			preamble: Vec::with_capacity(32 * capacity),
-			heap: Vec::new(),
+			capacity: Some(32 * capacity),
+			preamble: Vec::new(),
+			offsets: Vec::new(),
+			buffer: vec![0u8; 32 * capacity],
+			frame: Some((0, 0)),
+			depth: 0,
+			sized: false,
+			legacy_head: Vec::new(),
+			legacy_tail: Vec::new(),
 		}
 	}
 
-	fn top_ptr(&self) -> usize {
-		self.preamble.capacity() + self.heap.len()
+	/// New sink with known capacity (number of words in the preamble) and size of the
+	/// encoding in bytes (the sum of `Sink::size_of` of the values to be pushed), so that
+	/// the buffer is allocated once
+	pub fn with_size(capacity: usize, size: usize) -> Self {
+		Sink::with_prefix(&[], capacity, size)
 	}
 
-	/// Consume `val` to the Sink
-	pub fn push<T: AbiType>(&mut self, val: T) {
+	/// Same as `with_size`, with the encoding preceded by the given bytes (e.g. the function
+	/// selector) in the buffer, which the offsets and the capacity don't include
+	pub fn with_prefix(prefix: &[u8], capacity: usize, size: usize) -> Self {
+		let mut buffer = Vec::with_capacity(prefix.len() + size);
+		buffer.extend_from_slice(prefix);
+		buffer.resize(prefix.len() + 32 * capacity, 0);
+		Sink {
+			capacity: Some(32 * capacity),
+			preamble: Vec::new(),
+			offsets: Vec::new(),
+			buffer: buffer,
+			frame: Some((prefix.len(), prefix.len())),
+			depth: 0,
+			sized: true,
+			legacy_head: Vec::new(),
+			legacy_tail: Vec::new(),
+		}
+	}
+
+	/// Number of bytes the value takes when pushed to the sink: its head and, for the
+	/// dynamic types, its encoding after the heads
+	pub fn size_of<T: AbiEncode>(val: &T) -> usize {
 		if T::IS_FIXED {
-			val.encode(self)
+			T::HEAD_SIZE
 		} else {
-			let mut nested_sink = Sink::new(1);
-			val.encode(&mut nested_sink);
-			let top_ptr = self.top_ptr() as u32;
-			nested_sink.drain_to(&mut self.heap);
-			self.push(top_ptr);
+			32 + val.encoded_size()
 		}
 	}
 
-	/// Drain current Sink to the target vector
-	pub fn drain_to(self, target: &mut Vec<u8>) {
-		let preamble = self.preamble;
-		let heap = self.heap;
-		target.reserve(preamble.len() + heap.len());
-		target.extend_from_slice(&preamble);
-		target.extend_from_slice(&heap);
+	/// New sink without declared capacity, preamble size is computed from the pushed values
+	pub fn empty() -> Self {
+		Sink {
+			capacity: None,
+			preamble: Vec::new(),
+			offsets: Vec::new(),
+			buffer: Vec::new(),
+			frame: None,
+			depth: 0,
+			sized: false,
+			legacy_head: Vec::new(),
+			legacy_tail: Vec::new(),
+		}
 	}
 
-	/// Consume current Sink to produce a vector with content.
-	/// May panic if declared number of arguments does not match the resulting number of bytes should be produced.
-	pub fn finalize_panicking(self) -> Vec<u8> {
-		if self.preamble.len() != self.capacity { panic!("Underflow of pushed parameters {}/{}!", self.preamble.len(), self.capacity); }
-		let mut result = self.preamble;
-		let heap = self.heap;
+	/// Push `val` to the Sink, either owned or borrowed (e.g. `sink.push(&value)`)
+	pub fn push<T: AbiEncode>(&mut self, val: T) {
+		self.flush_legacy();
+		if T::IS_FIXED {
+			val.encode(self);
+			return self.flush_legacy();
+		}
 
-		result.extend_from_slice(&heap);
-		result
+		if self.depth == 0 && !self.sized {
+			// nested values are encoded within the space reserved for the top-level one
+			self.buffer.reserve(val.encoded_size());
+		}
+		match self.frame {
+			Some((start, _)) => {
+				let offset = util::pad_u32((self.buffer.len() - start) as u32);
+				self.write(&offset[..]);
+			},
+			None => {
+				self.offsets.push((self.preamble.len(), self.buffer.len()));
+				// placeholder, actual offset is known only when all heads are pushed
+				self.preamble.extend_from_slice(&[0u8; 32]);
+			},
+		}
+
+		let parent = self.frame;
+		self.frame = Some((self.buffer.len(), self.buffer.len()));
+		self.depth += 1;
+		val.encode(self);
+		self.flush_legacy();
+		self.depth -= 1;
+		self.frame = parent;
 	}
 
-	/// Mutable reference to the Sink preamble
+	/// Push members of the variable-size array (`T[]`), which offsets are relative to the
+	/// start of the members rather than to the start of the array
+	pub fn push_members<T: AbiEncode, I: ExactSizeIterator<Item=T>>(&mut self, members: I) {
+		self.flush_legacy();
+		let parent = self.frame;
+		self.frame = Some((self.buffer.len(), self.buffer.len()));
+		self.reserve_head(members.len() * T::HEAD_SIZE);
+		for member in members {
+			self.push(member);
+		}
+		self.frame = parent.map(|(start, _)| (start, self.buffer.len()));
+	}
+
+	/// Reserve `len` bytes for the heads of the members of the tuple-like value being encoded.
+	/// Should be called by the encoders of such types before pushing the members, so that
+	/// the dynamic members are appended after all the heads.
+	pub fn reserve_head(&mut self, len: usize) {
+		self.flush_legacy();
+		if let Some((_, head)) = self.frame {
+			if self.buffer.len() < head + len {
+				self.buffer.resize(head + len, 0);
+			}
+		}
+	}
+
+	/// Write raw bytes to the head of the value being encoded
+	pub fn write(&mut self, data: &[u8]) {
+		self.flush_legacy();
+		self.write_head(data);
+	}
+
+	fn write_head(&mut self, data: &[u8]) {
+		match self.frame {
+			Some((start, head)) => {
+				let end = head + data.len();
+				if self.buffer.len() < end {
+					self.buffer.resize(end, 0);
+				}
+				self.buffer[head..end].copy_from_slice(data);
+				self.frame = Some((start, end));
+			},
+			None => self.preamble.extend_from_slice(data),
+		}
+	}
+
+	/// Size of the pushed top-level heads in bytes
+	fn preamble_len(&self) -> usize {
+		match (self.capacity, self.frame) {
+			(Some(_), Some((start, head))) => head - start,
+			_ => self.preamble.len(),
+		}
+	}
+
+	/// Writes offsets of the dynamic values, which are relative to the start of the preamble
+	fn write_offsets(&mut self) {
+		let preamble_len = self.preamble.len();
+		for &(position, buffer_position) in self.offsets.iter() {
+			let offset = util::pad_u32((preamble_len + buffer_position) as u32);
+			self.preamble[position..position + 32].copy_from_slice(&offset[..]);
+		}
+	}
+
+	/// Mutable reference to the head of the value being encoded, the bytes pushed to it
+	/// are written to the head by the next operation on the sink
+	#[deprecated(note = "use `write` for the heads and `push` for the dynamic members")]
 	pub fn preamble_mut(&mut self) -> &mut Vec<u8> {
-		&mut self.preamble
+		self.flush_legacy();
+		&mut self.legacy_head
 	}
 
-	/// Mutable reference to the Sink heap
+	/// Mutable reference to the tail of the value being encoded, the bytes pushed to it
+	/// are appended to the tail by the next operation on the sink. Offsets of such data
+	/// should be written by the caller.
+	#[deprecated(note = "use `push` for the dynamic members, which writes their offsets")]
 	pub fn heap_mut(&mut self) -> &mut Vec<u8> {
-		&mut self.heap
+		self.flush_legacy();
+		&mut self.legacy_tail
+	}
+
+	/// Moves the bytes written through `preamble_mut` and `heap_mut` to the buffer
+	fn flush_legacy(&mut self) {
+		if !self.legacy_head.is_empty() {
+			let head = mem::replace(&mut self.legacy_head, Vec::new());
+			self.write_head(&head);
+		}
+		if !self.legacy_tail.is_empty() {
+			let tail = mem::replace(&mut self.legacy_tail, Vec::new());
+			self.buffer.extend_from_slice(&tail);
+		}
+	}
+
+	/// Drain current Sink to the target vector
+	pub fn drain_to(mut self, target: &mut Vec<u8>) {
+		self.flush_legacy();
+		self.write_offsets();
+		target.reserve(self.preamble.len() + self.buffer.len());
+		target.extend_from_slice(&self.preamble);
+		target.extend_from_slice(&self.buffer);
+	}
+
+	/// Consume current Sink to produce a vector with content.
+	/// Fails if declared capacity does not match the resulting number of bytes in the preamble.
+	pub fn finalize(mut self) -> Result<Vec<u8>, CapacityMismatch> {
+		self.flush_legacy();
+		if let Some(capacity) = self.capacity {
+			let pushed = self.preamble_len();
+			if pushed != capacity {
+				return Err(CapacityMismatch { declared: capacity, pushed: pushed });
+			}
+			return Ok(self.buffer);
+		}
+		self.write_offsets();
+		let mut result = self.preamble;
+		result.extend_from_slice(&self.buffer);
+		Ok(result)
+	}
+
+	/// Consume current Sink to produce a vector with content.
+	/// May panic if declared number of arguments does not match the resulting number of bytes should be produced.
+	pub fn finalize_panicking(self) -> Vec<u8> {
+		match self.finalize() {
+			Ok(result) => result,
+			Err(err) => panic!("Underflow of pushed parameters {}/{}!", err.pushed, err.declared),
+		}
 	}
 }
@@ -1,46 +1,224 @@
 //! Stream module
 
 use lib::*;
-use super::{AbiType, Error};
+use super::{AbiType, AbiDecode, AbiEncode, FixedAbiType, Error, ErrorKind};
+
+/// Limits of the decoding, protecting from payloads requesting excessive memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+	/// Maximum number of elements in a single array
+	pub max_elements: usize,
+	/// Maximum total number of bytes allocated by the decoded values
+	pub max_bytes: usize,
+	/// Maximum nesting depth of the dynamic values: every dynamic value (e.g. `bytes`,
+	/// `T[]` or a dynamic tuple) counts as one level, including the top-level ones,
+	/// so `string[][]` takes 3 levels
+	pub max_depth: usize,
+}
+
+impl Default for Limits {
+	fn default() -> Self {
+		Limits {
+			max_elements: 64 * 1024,
+			max_bytes: 1024 * 1024,
+			max_depth: 32,
+		}
+	}
+}
 
 /// Stream interpretation of incoming payload
 pub struct Stream<'a> {
 	payload: &'a [u8],
 	position: usize,
+	limits: Limits,
+	depth: usize,
+	allocated: usize,
+	strict: bool,
+	/// Position of the stream payload in the payload of the parent stream
+	origin: usize,
+	/// Position of the stream payload in the payload of the root stream
+	base: usize,
+	/// Offset of the first dynamic value
+	first_offset: Option<usize>,
+	/// End of the dynamic values (tail)
+	tail_end: Option<usize>,
 }
 
 impl<'a> Stream<'a> {
 
 	/// New stream for known payload
+	///
+	/// Stream is strict (see `set_strict`) if the `strict-decoding` feature is enabled.
 	pub fn new(raw: &'a [u8]) -> Self {
+		Stream::with_limits(raw, Limits::default())
+	}
+
+	/// New stream for known payload with the given decoding limits
+	pub fn with_limits(raw: &'a [u8], limits: Limits) -> Self {
 		Stream {
 			payload: raw,
 			position: 0,
+			limits: limits,
+			depth: 0,
+			allocated: 0,
+			strict: cfg!(feature = "strict-decoding"),
+			origin: 0,
+			base: 0,
+			first_offset: None,
+			tail_end: None,
 		}
 	}
 
-	/// Pop next argument of known type
-	pub fn pop<T: AbiType>(&mut self) -> Result<T, Error> {
+	/// Set whether the stream is strict, rejecting non-canonical encodings:
+	/// non-zero padding, offsets not pointing right past the head or the previous
+	/// dynamic value, and trailing bytes (see `finish`).
+	pub fn set_strict(&mut self, strict: bool) {
+		self.strict = strict;
+	}
+
+	/// Whether the stream is strict
+	pub fn is_strict(&self) -> bool { self.strict }
+
+	/// Pop next argument of known type, which can borrow from the payload (`&'a [u8]` or `&'a str`)
+	///
+	/// Errors carry the position of the value (of its data for dynamic values)
+	/// and its canonical abi type (with the `alloc` feature).
+	pub fn pop<T: AbiDecode<'a>>(&mut self) -> Result<T, Error> {
+		let position = self.base + self.position;
 		if T::IS_FIXED {
-			T::decode(self)
+			T::decode_borrowed(self).map_err(|err| at::<T>(err, position))
 		} else {
-			let offset = u32::decode(self)?;
-			let mut nested_stream = Stream::new(&self.payload[offset as usize..]);
-			T::decode(&mut nested_stream)
+			self.pop_dynamic().map_err(|err| at::<T>(err, position))
+		}
+	}
+
+	/// Pop next argument of known fixed-size type, without allocation even on failure
+	///
+	/// Errors carry only the position of the value, since the canonical abi type is allocated.
+	pub fn pop_fixed<T: FixedAbiType>(&mut self) -> Result<T, Error> {
+		let position = self.base + self.position;
+		T::decode_fixed(self).map_err(|err| err.at_position(position))
+	}
+
+	fn pop_dynamic<T: AbiDecode<'a>>(&mut self) -> Result<T, Error> {
+		let offset = u32::decode(self)? as usize;
+		if self.strict {
+			let canonical = match self.tail_end {
+				Some(tail_end) => offset == tail_end,
+				None => offset >= self.position,
+			};
+			if !canonical {
+				return Err(ErrorKind::InvalidOffset.into());
+			}
+			if self.first_offset.is_none() {
+				self.first_offset = Some(offset);
+			}
+		}
+		let mut nested_stream = self.nested(offset)?;
+		let data_position = nested_stream.base;
+		let result = T::decode_borrowed(&mut nested_stream)
+			.map_err(|err| at::<T>(err, data_position))?;
+		self.join(nested_stream)?;
+		Ok(result)
+	}
+
+	/// Nested stream for the dynamic value at the given offset, sharing the
+	/// limits with this stream
+	///
+	/// Should be joined back (`join`) after decoding.
+	pub fn nested(&self, offset: usize) -> Result<Stream<'a>, Error> {
+		if self.depth >= self.limits.max_depth {
+			return Err(ErrorKind::LimitExceeded.into());
 		}
+		self.substream(offset, self.depth + 1)
+	}
+
+	/// Stream of the members of the variable-size array, starting at the current position
+	/// (past the length), which offsets of the dynamic members are relative to
+	///
+	/// Unlike `nested`, is not counted as a level of nesting, since the array itself is.
+	/// Should be joined back (`join`) after decoding.
+	pub fn members(&self) -> Result<Stream<'a>, Error> {
+		self.substream(self.position, self.depth)
+	}
+
+	fn substream(&self, offset: usize, depth: usize) -> Result<Stream<'a>, Error> {
+		Ok(Stream {
+			payload: self.payload.get(offset..).ok_or(ErrorKind::UnexpectedEof)?,
+			position: 0,
+			limits: self.limits,
+			depth: depth,
+			allocated: self.allocated,
+			strict: self.strict,
+			origin: offset,
+			base: self.base + offset,
+			first_offset: None,
+			tail_end: None,
+		})
 	}
 
+	/// Join back the nested stream, accounting for its allocations and extent
+	pub fn join(&mut self, nested: Stream<'a>) -> Result<(), Error> {
+		if self.strict {
+			nested.check_head()?;
+		}
+
+		self.allocated = nested.allocated;
+		let end = nested.origin + nested.extent();
+		self.tail_end = Some(cmp::max(self.tail_end.unwrap_or(0), end));
+		Ok(())
+	}
+
+	/// Finish decoding, in the strict mode checks that there are no trailing bytes
+	pub fn finish(&self) -> Result<(), Error> {
+		if self.strict {
+			self.check_head()?;
+			if self.extent() != self.payload.len() {
+				return Err(ErrorKind::TrailingBytes.into());
+			}
+		}
+		Ok(())
+	}
+
+	/// Dynamic values should start right past the head
+	fn check_head(&self) -> Result<(), Error> {
+		match self.first_offset {
+			Some(offset) if offset != self.position => Err(ErrorKind::InvalidOffset.into()),
+			_ => Ok(()),
+		}
+	}
+
+	/// End of the decoded data, including dynamic values
+	fn extent(&self) -> usize {
+		cmp::max(self.position, self.tail_end.unwrap_or(0))
+	}
+
+	/// Account for `bytes` to be allocated by the decoded value
+	pub fn allocate(&mut self, bytes: usize) -> Result<(), Error> {
+		let allocated = self.allocated.checked_add(bytes).ok_or(ErrorKind::LimitExceeded)?;
+		if allocated > self.limits.max_bytes {
+			return Err(ErrorKind::LimitExceeded.into());
+		}
+
+		self.allocated = allocated;
+		Ok(())
+	}
+
+	/// Decoding limits of the stream
+	pub fn limits(&self) -> &Limits { &self.limits }
+
 	/// Current position for the stream
 	pub fn position(&self) -> usize { self.position }
 
 	/// Advance stream position for `amount` bytes
 	pub fn advance(&mut self, amount: usize) -> Result<usize, Error> {
-		if self.position + amount > self.payload.len() {
-			return Err(Error::UnexpectedEof);
+		let new_position = self.position.checked_add(amount).ok_or(ErrorKind::UnexpectedEof)?;
+		if new_position > self.payload.len() {
+			return Err(ErrorKind::UnexpectedEof.into());
 		}
 
 		let old_position = self.position;
-		self.position += amount;
+		self.position = new_position;
 		Ok(old_position)
 	}
 
@@ -50,12 +228,29 @@ impl<'a> Stream<'a> {
 	}
 
 	/// Stream payload
-	pub fn payload(&self) -> &[u8] {
+	pub fn payload(&self) -> &'a [u8] {
 		self.payload
 	}
 
+	/// Stream payload starting at the current position
+	pub fn remaining(&self) -> Result<&'a [u8], Error> {
+		self.payload.get(self.position..).ok_or_else(|| ErrorKind::UnexpectedEof.into())
+	}
+
 	/// Peek next byte in stream
-	pub fn peek(&self) -> u8 {
-		self.payload[self.position]
+	pub fn peek(&self) -> Result<u8, Error> {
+		self.payload.get(self.position).cloned().ok_or_else(|| ErrorKind::UnexpectedEof.into())
 	}
 }
+
+/// Error with the position and the canonical abi type of the value of type `T`
+#[cfg(feature = "alloc")]
+fn at<T: AbiEncode>(err: Error, position: usize) -> Error {
+	err.at(position, T::canonical_name())
+}
+
+/// Error with the position of the value, since the canonical abi type is allocated
+#[cfg(not(feature = "alloc"))]
+fn at<T: AbiEncode>(err: Error, position: usize) -> Error {
+	err.at_position(position)
+}
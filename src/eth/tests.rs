@@ -5,6 +5,7 @@ use lib::*;
 #[cfg(feature = "std")]
 mod hextest {
 	use super::super::*;
+	use super::super::types::*;
 	use lib::*;
 
 	#[test]
@@ -41,6 +42,135 @@ mod hextest {
 		assert_eq!(bytes2, hex!("0010000000000000000000000000000000000000000000000000000000000002"));
 	}
 
+	#[test]
+	fn nested_dynamic_tuple() {
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000002
+			6162000000000000000000000000000000000000000000000000000000000000
+		");
+
+		assert_eq!(super::single_encode(((1u32, String::from("ab")), 2u64)), encoded.to_vec());
+
+		let ((v1, v2), v3): ((u32, String), u64) = super::single_decode(&encoded);
+		assert_eq!(v1, 1);
+		assert_eq!(v2, "ab");
+		assert_eq!(v3, 2);
+	}
+
+	#[test]
+	fn dynamic_tuple_array_encoding() {
+		// abi.encode([(1, "a"), (2, "bc")]) for (uint32,string)[]
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000040
+			00000000000000000000000000000000000000000000000000000000000000c0
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000001
+			6100000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000002
+			6263000000000000000000000000000000000000000000000000000000000000
+		");
+		let value = vec![(1u32, String::from("a")), (2u32, String::from("bc"))];
+
+		assert_eq!(super::single_encode(value.clone()), encoded.to_vec());
+		assert_eq!(super::single_decode::<Vec<(u32, String)>>(&encoded), value);
+	}
+
+	#[test]
+	fn dynamic_tuple_array_in_tuple_encoding() {
+		// abi.encode((7, [(1, "a")])) for (uint32,(uint32,string)[])
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000007
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000001
+			6100000000000000000000000000000000000000000000000000000000000000
+		");
+		let value = (7u32, vec![(1u32, String::from("a"))]);
+
+		assert_eq!(super::single_encode(value.clone()), encoded.to_vec());
+		assert_eq!(super::single_decode::<(u32, Vec<(u32, String)>)>(&encoded), value);
+	}
+
+	#[test]
+	fn fixed_array_static() {
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000003
+			0000000000000000000000000000000000000000000000000000000000000004
+		");
+
+		let mut sink = Sink::new(4);
+		sink.push([U256::from(1), U256::from(2), U256::from(3)]);
+		sink.push(4u32);
+		assert_eq!(sink.finalize_panicking(), encoded.to_vec());
+
+		let (array, value) = super::double_decode::<[U256; 3], u32>(&encoded);
+		assert_eq!(array, [U256::from(1), U256::from(2), U256::from(3)]);
+		assert_eq!(value, 4);
+	}
+
+	#[test]
+	fn fixed_array_dynamic() {
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000080
+			0000000000000000000000000000000000000000000000000000000000000001
+			6100000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000002
+			6263000000000000000000000000000000000000000000000000000000000000
+		");
+
+		assert_eq!(super::single_encode([String::from("a"), String::from("bc")]), encoded.to_vec());
+
+		let array: [String; 2] = super::single_decode(&encoded);
+		assert_eq!(array, [String::from("a"), String::from("bc")]);
+	}
+
+	#[test]
+	fn nested_bytes_array() {
+		let encoded = hex!("
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000040
+			00000000000000000000000000000000000000000000000000000000000000c0
+			0000000000000000000000000000000000000000000000000000000000000001
+			0000000000000000000000000000000000000000000000000000000000000020
+			0000000000000000000000000000000000000000000000000000000000000001
+			6100000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000002
+			0000000000000000000000000000000000000000000000000000000000000040
+			0000000000000000000000000000000000000000000000000000000000000080
+			0000000000000000000000000000000000000000000000000000000000000002
+			6263000000000000000000000000000000000000000000000000000000000000
+			0000000000000000000000000000000000000000000000000000000000000000
+		");
+		let value = vec![
+			vec![b"a".to_vec()],
+			vec![b"bc".to_vec(), Vec::new()],
+		];
+
+		assert_eq!(super::single_encode(value.clone()), encoded.to_vec());
+
+		let decoded: Vec<Vec<Vec<u8>>> = super::single_decode(&encoded);
+		assert_eq!(decoded, value);
+	}
+
 	#[test]
 	fn bytes_encode() {
 		assert_eq!(
@@ -97,6 +227,15 @@ fn triple_decode<T1: super::AbiType, T2: super::AbiType, T3: super::AbiType>(pay
 	)
 }
 
+/// Parses hex encoded fixture, skipping whitespace and `//` comments
+fn fixture(source: &str) -> Vec<u8> {
+	let digits: Vec<u8> = source.lines()
+		.flat_map(|line| line.split("//").next().unwrap_or("").trim().chars())
+		.map(|c| c.to_digit(16).expect("fixture should contain only hex digits") as u8)
+		.collect();
+	digits.chunks(2).map(|pair| (pair[0] << 4) | pair[1]).collect()
+}
+
 fn single_encode<T: super::AbiType>(val: T) -> Vec<u8> {
 	let mut sink = super::Sink::new(1);
 	sink.push(val);
@@ -217,7 +356,7 @@ fn padding_test_i32() {
 	let mut sample = [0xff; 32];
 	sample[0] = 0x80;
 	let mut stream = ::eth::Stream::new(&sample);
-	assert_eq!(stream.pop::<i32>().unwrap_err(), Error::InvalidPadding);
+	assert_eq!(stream.pop::<i32>().unwrap_err().kind(), ErrorKind::InvalidPadding);
 }
 
 #[test]
@@ -225,7 +364,7 @@ fn padding_test_i64() {
 	let mut sample = [0xff; 32];
 	sample[0] = 0x80;
 	let mut stream = ::eth::Stream::new(&sample);
-	assert_eq!(stream.pop::<i64>().unwrap_err(), Error::InvalidPadding);
+	assert_eq!(stream.pop::<i64>().unwrap_err().kind(), ErrorKind::InvalidPadding);
 }
 
 
@@ -257,3 +396,946 @@ fn string_encode_decode() {
 	let output: String = stream.pop().expect("Test string failed to pop");
 	assert_eq!(test_string, output);
 }
+
+#[test]
+fn small_uint_encode_decode() {
+	let mut sink = Sink::new(3);
+	sink.push(0xabu8);
+	sink.push(0xabcdu16);
+	sink.push(u128::max_value());
+	let payload = sink.finalize_panicking();
+
+	let mut expected = [0u8; 96];
+	expected[31] = 0xab;
+	expected[62] = 0xab;
+	expected[63] = 0xcd;
+	for byte in expected[80..96].iter_mut() { *byte = 0xff; }
+	assert_eq!(&payload[..], &expected[..]);
+
+	let (v1, v2, v3) = triple_decode::<u8, u16, u128>(&payload);
+	assert_eq!(v1, 0xab);
+	assert_eq!(v2, 0xabcd);
+	assert_eq!(v3, u128::max_value());
+}
+
+#[test]
+fn small_uint_out_of_range() {
+	let mut sample = [0u8; 32];
+	sample[30] = 0x01;
+	assert_eq!(Stream::new(&sample).pop::<u8>().unwrap_err().kind(), ErrorKind::InvalidU8);
+	assert_eq!(Stream::new(&sample).pop::<u16>().unwrap(), 0x0100);
+
+	sample[29] = 0x01;
+	assert_eq!(Stream::new(&sample).pop::<u16>().unwrap_err().kind(), ErrorKind::InvalidU16);
+
+	sample[15] = 0x01;
+	assert_eq!(Stream::new(&sample).pop::<u128>().unwrap_err().kind(), ErrorKind::InvalidU128);
+}
+
+#[test]
+fn small_int_encode_decode() {
+	let mut sink = Sink::new(3);
+	sink.push(-2i8);
+	sink.push(i16::min_value());
+	sink.push(i128::max_value());
+	let payload = sink.finalize_panicking();
+
+	let mut expected = [0xffu8; 96];
+	expected[31] = 0xfe;
+	expected[62] = 0x80;
+	expected[63] = 0x00;
+	for byte in expected[64..80].iter_mut() { *byte = 0x00; }
+	expected[80] = 0x7f;
+	assert_eq!(&payload[..], &expected[..]);
+
+	let (v1, v2, v3) = triple_decode::<i8, i16, i128>(&payload);
+	assert_eq!(v1, -2);
+	assert_eq!(v2, i16::min_value());
+	assert_eq!(v3, i128::max_value());
+}
+
+#[test]
+fn small_int_sign_extension() {
+	// 0x80 in the lowest byte is negative for int8, so padding should be 0xff
+	let mut sample = [0u8; 32];
+	sample[31] = 0x80;
+	assert_eq!(Stream::new(&sample).pop::<i8>().unwrap_err().kind(), ErrorKind::InvalidPadding);
+	assert_eq!(Stream::new(&sample).pop::<i16>().unwrap(), 0x80);
+
+	let mut sample = [0xffu8; 32];
+	sample[31] = 0x7f;
+	assert_eq!(Stream::new(&sample).pop::<i8>().unwrap_err().kind(), ErrorKind::InvalidPadding);
+	assert_eq!(Stream::new(&sample).pop::<i128>().unwrap(), -129);
+}
+
+#[test]
+fn i256_encode_decode() {
+	let mut sink = Sink::new(2);
+	sink.push(I256::from(-2i64));
+	sink.push(I256::max_value());
+	let payload = sink.finalize_panicking();
+
+	let mut expected = [0xffu8; 64];
+	expected[31] = 0xfe;
+	expected[32] = 0x7f;
+	assert_eq!(&payload[..], &expected[..]);
+
+	let (v1, v2) = double_decode::<I256, I256>(&payload);
+	assert_eq!(v1, I256::from(-2i64));
+	assert_eq!(v2, I256::max_value());
+}
+
+#[test]
+fn tuple_encode_decode() {
+	let mut sink = Sink::new(2);
+	sink.push((2u64, true));
+	let payload = sink.finalize_panicking();
+
+	let value: (u64, bool) = single_decode(&payload);
+	assert_eq!(value, (2, true));
+}
+
+#[test]
+fn tuple_dynamic_member_decode() {
+	let mut sink = Sink::new(1);
+	sink.push((6u64, vec![1u8, 2, 3, 5, 7, 11], String::from("eleven")));
+	let payload = sink.finalize_panicking();
+
+	let (v1, v2, v3): (u64, Vec<u8>, String) = single_decode(&payload);
+	assert_eq!(v1, 6);
+	assert_eq_core!(v2, vec![1u8, 2, 3, 5, 7, 11]);
+	assert_eq_core!(v3, String::from("eleven"));
+}
+
+#[test]
+fn tuple_is_fixed() {
+	assert!(<(u64, bool) as AbiEncode>::IS_FIXED);
+	assert!(!<(u64, Vec<u8>) as AbiEncode>::IS_FIXED);
+	assert!(!<(u32, (bool, String)) as AbiEncode>::IS_FIXED);
+}
+
+#[test]
+fn dynamic_tuple_array() {
+	let value = vec![
+		(1u32, String::from("one")),
+		(2u32, String::from("two")),
+		(3u32, String::from("three")),
+	];
+
+	let payload = single_encode(value.clone());
+	let decoded: Vec<(u32, String)> = single_decode(&payload);
+
+	assert_eq_core!(decoded, value);
+}
+
+#[test]
+fn fixed_array_is_fixed() {
+	assert!(<[U256; 3] as AbiEncode>::IS_FIXED);
+	assert!(<[[H160; 2]; 2] as AbiEncode>::IS_FIXED);
+	assert!(!<[String; 4] as AbiEncode>::IS_FIXED);
+	assert!(!<[Vec<u32>; 1] as AbiEncode>::IS_FIXED);
+}
+
+#[test]
+fn nested_fixed_array() {
+	let value = [
+		[Address::from([0x11u8; 20]), Address::from([0x22u8; 20])],
+		[Address::from([0x33u8; 20]), Address::from([0x44u8; 20])],
+	];
+
+	let mut sink = Sink::new(4);
+	sink.push(value);
+	let payload = sink.finalize_panicking();
+
+	let decoded: [[Address; 2]; 2] = single_decode(&payload);
+	assert_eq!(decoded, value);
+}
+
+#[test]
+fn fixture_f() {
+	let encoded = fixture(include_str!("../../res/fixtures/f.abi"));
+
+	let mut sink = Sink::new(4);
+	sink.push(U256::from(0x123));
+	sink.push(vec![0x456u32, 0x789]);
+	sink.push(*b"1234567890");
+	sink.push(b"Hello, world!".to_vec());
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let mut stream = Stream::new(&encoded);
+	assert_eq!(stream.pop::<U256>().unwrap(), U256::from(0x123));
+	assert_eq_core!(stream.pop::<Vec<u32>>().unwrap(), vec![0x456u32, 0x789]);
+	assert_eq!(&stream.pop::<[u8; 10]>().unwrap(), b"1234567890");
+	assert_eq_core!(stream.pop::<Vec<u8>>().unwrap(), b"Hello, world!".to_vec());
+}
+
+#[test]
+fn fixture_g() {
+	let encoded = fixture(include_str!("../../res/fixtures/g.abi"));
+	let numbers = vec![
+		vec![U256::from(1), U256::from(2)],
+		vec![U256::from(3)],
+	];
+	let strings = vec![
+		String::from("one"),
+		String::from("two"),
+		String::from("three"),
+	];
+
+	let mut sink = Sink::new(2);
+	sink.push(numbers.clone());
+	sink.push(strings.clone());
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let (v1, v2) = double_decode::<Vec<Vec<U256>>, Vec<String>>(&encoded);
+	assert_eq_core!(v1, numbers);
+	assert_eq_core!(v2, strings);
+}
+
+#[test]
+fn fixture_sam() {
+	let encoded = fixture(include_str!("../../res/fixtures/sam.abi"));
+
+	let mut sink = Sink::new(3);
+	sink.push(b"dave".to_vec());
+	sink.push(true);
+	sink.push(vec![U256::from(1), U256::from(2), U256::from(3)]);
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let (v1, v2, v3) = triple_decode::<Vec<u8>, bool, Vec<U256>>(&encoded);
+	assert_eq_core!(v1, b"dave".to_vec());
+	assert_eq!(v2, true);
+	assert_eq_core!(v3, vec![U256::from(1), U256::from(2), U256::from(3)]);
+}
+
+#[test]
+fn fixture_transfers() {
+	let encoded = fixture(include_str!("../../res/fixtures/transfers.abi"));
+	let transfers = vec![
+		(U256::from(1), String::from("one")),
+		(U256::from(2), String::from("two")),
+	];
+
+	let mut sink = Sink::new(1);
+	sink.push(transfers.clone());
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let decoded: Vec<(U256, String)> = single_decode(&encoded);
+	assert_eq_core!(decoded, transfers);
+}
+
+#[test]
+fn fixture_nested() {
+	let encoded = fixture(include_str!("../../res/fixtures/nested.abi"));
+	let nested = (U256::from(7), (String::from("abc"), vec![U256::from(1), U256::from(2)]));
+
+	let mut sink = Sink::new(2);
+	sink.push(nested.clone());
+	sink.push(true);
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let (v1, v2) = double_decode::<(U256, (String, Vec<U256>)), bool>(&encoded);
+	assert_eq_core!(v1, nested);
+	assert_eq!(v2, true);
+}
+
+#[test]
+fn fixture_blobs() {
+	let encoded = fixture(include_str!("../../res/fixtures/blobs.abi"));
+	let blobs = vec![b"dave".to_vec(), Vec::new(), (0..33).collect::<Vec<u8>>()];
+
+	let mut sink = Sink::new(1);
+	sink.push(blobs.clone());
+	assert_eq_core!(sink.finalize_panicking(), encoded);
+
+	let decoded: Vec<Vec<u8>> = single_decode(&encoded);
+	assert_eq_core!(decoded, blobs);
+}
+
+#[test]
+fn canonical_names() {
+	assert_eq!(u8::canonical_name(), "uint8");
+	assert_eq!(i128::canonical_name(), "int128");
+	assert_eq!(I256::canonical_name(), "int256");
+	assert_eq!(H160::canonical_name(), "address");
+	assert_eq!(H256::canonical_name(), "bytes32");
+	assert_eq!(<[u8; 4]>::canonical_name(), "bytes4");
+	assert_eq!(<Vec<u8>>::canonical_name(), "bytes");
+	assert_eq!(<Vec<Vec<U256>>>::canonical_name(), "uint256[][]");
+	assert_eq!(<[String; 2]>::canonical_name(), "string[2]");
+	assert_eq!(<(u32, Vec<bool>)>::canonical_name(), "(uint32,bool[])");
+	assert_eq!(<Vec<(H160, [U256; 3])>>::canonical_name(), "(address,uint256[3])[]");
+}
+
+/// Simple xorshift generator, so that property tests are reproducible
+struct Random(u64);
+
+impl Random {
+	fn next(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	/// Random 32-byte word, biased towards values meaningful for the decoder
+	/// (small lengths and offsets, padding, huge lengths)
+	fn word(&mut self) -> [u8; 32] {
+		let mut word = [0u8; 32];
+		match self.next() % 6 {
+			0 => {},
+			1 => word[31] = (self.next() % 0x100) as u8,
+			2 => word[30..].copy_from_slice(&[0x01, (self.next() % 0x100) as u8]),
+			3 => word[28..].copy_from_slice(&[0xff; 4]),
+			4 => word = [0xff; 32],
+			_ => for byte in word.iter_mut() { *byte = self.next() as u8; },
+		}
+		word
+	}
+
+	fn payload(&mut self) -> Vec<u8> {
+		let words = self.next() % 12;
+		let mut payload = Vec::new();
+		for _ in 0..words {
+			payload.extend_from_slice(&self.word());
+		}
+		// occasionally truncated in the middle of the word
+		if self.next() % 4 == 0 && !payload.is_empty() {
+			let len = (self.next() as usize) % payload.len();
+			payload.truncate(len);
+		}
+		payload
+	}
+}
+
+macro_rules! decode_all {
+	($payload: expr, $($t: ty),+) => {
+		$(
+			let _ = Stream::new($payload).pop::<$t>();
+			let _ = <$t as AbiType>::decode(&mut Stream::new($payload));
+		)+
+	}
+}
+
+#[test]
+fn random_payloads_do_not_panic() {
+	let mut random = Random(0x2545_f491_4f6c_dd1d);
+	for _ in 0..5000 {
+		let payload = random.payload();
+		decode_all!(&payload[..],
+			u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, bool,
+			U256, I256, H160, H256, [u8; 4], [u8; 32],
+			Vec<u8>, String, Vec<U256>, Vec<i32>, Vec<Vec<u8>>, Vec<String>, Vec<Vec<Vec<u32>>>,
+			[U256; 3], [String; 2], [Vec<u8>; 2], Vec<[u64; 2]>,
+			(u32, Vec<u8>), (Vec<String>, bool), Vec<(H160, String)>, (bool, (String, [Vec<u32>; 2]))
+		);
+	}
+}
+
+#[test]
+fn limits_elements() {
+	let mut sink = Sink::new(1);
+	sink.push(vec![0u32; 10]);
+	let encoded = sink.finalize_panicking();
+
+	let limits = Limits { max_elements: 9, ..Limits::default() };
+	assert_eq!(Stream::with_limits(&encoded, limits).pop::<Vec<u32>>().map_err(|err| err.kind()), Err(ErrorKind::LimitExceeded));
+
+	let limits = Limits { max_elements: 10, ..Limits::default() };
+	assert_eq_core!(Stream::with_limits(&encoded, limits).pop::<Vec<u32>>().unwrap(), vec![0u32; 10]);
+}
+
+#[test]
+fn limits_bytes() {
+	// 64 members of `bytes[]` all pointing to the same 1 KiB of data
+	let mut encoded = Vec::new();
+	encoded.extend_from_slice(&util::pad_u32(0x20));
+	encoded.extend_from_slice(&util::pad_u32(64));
+	for _ in 0..64 {
+		encoded.extend_from_slice(&util::pad_u32(64 * 32));
+	}
+	encoded.extend_from_slice(&util::pad_u32(1024));
+	encoded.extend_from_slice(&[0xab; 1024]);
+
+	let limits = Limits { max_bytes: 32 * 1024, ..Limits::default() };
+	let mut stream = Stream::with_limits(&encoded, limits);
+	stream.set_strict(false);
+	assert_eq!(stream.pop::<Vec<Vec<u8>>>().map_err(|err| err.kind()), Err(ErrorKind::LimitExceeded));
+
+	let decoded = lenient_stream(&encoded).pop::<Vec<Vec<u8>>>().unwrap();
+	assert_eq!(decoded.len(), 64);
+	assert_eq_core!(decoded[63], vec![0xab; 1024]);
+}
+
+#[test]
+fn limits_depth() {
+	let mut sink = Sink::new(1);
+	sink.push(vec![vec![String::from("nested")]]);
+	let encoded = sink.finalize_panicking();
+
+	// one level for each of the arrays and the string
+	let limits = Limits { max_depth: 2, ..Limits::default() };
+	assert_eq!(Stream::with_limits(&encoded, limits).pop::<Vec<Vec<String>>>().map_err(|err| err.kind()), Err(ErrorKind::LimitExceeded));
+
+	let limits = Limits { max_depth: 3, ..Limits::default() };
+	assert_eq_core!(
+		Stream::with_limits(&encoded, limits).pop::<Vec<Vec<String>>>().unwrap(),
+		vec![vec![String::from("nested")]]
+	);
+
+	// fixed arrays of the fixed types are not nested
+	let limits = Limits { max_depth: 1, ..Limits::default() };
+	let mut sink = Sink::new(1);
+	sink.push(vec![[U256::from(1); 2]]);
+	let encoded = sink.finalize_panicking();
+	assert_eq!(Stream::with_limits(&encoded, limits).pop::<Vec<[U256; 2]>>().unwrap(), vec![[U256::from(1); 2]]);
+	assert_eq!(Stream::with_limits(&encoded, Limits { max_depth: 0, ..limits }).pop::<Vec<[U256; 2]>>().map_err(|err| err.kind()), Err(ErrorKind::LimitExceeded));
+}
+
+fn strict_stream(payload: &[u8]) -> Stream {
+	let mut stream = Stream::new(payload);
+	stream.set_strict(true);
+	stream
+}
+
+fn lenient_stream(payload: &[u8]) -> Stream {
+	let mut stream = Stream::new(payload);
+	stream.set_strict(false);
+	stream
+}
+
+#[test]
+fn strict_fixtures() {
+	let encoded = fixture(include_str!("../../res/fixtures/f.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<U256>().unwrap();
+	stream.pop::<Vec<u32>>().unwrap();
+	stream.pop::<[u8; 10]>().unwrap();
+	stream.pop::<Vec<u8>>().unwrap();
+	stream.finish().unwrap();
+
+	let encoded = fixture(include_str!("../../res/fixtures/g.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<Vec<Vec<U256>>>().unwrap();
+	stream.pop::<Vec<String>>().unwrap();
+	stream.finish().unwrap();
+
+	let encoded = fixture(include_str!("../../res/fixtures/sam.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<Vec<u8>>().unwrap();
+	stream.pop::<bool>().unwrap();
+	stream.pop::<Vec<U256>>().unwrap();
+	stream.finish().unwrap();
+
+	let encoded = fixture(include_str!("../../res/fixtures/transfers.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<Vec<(U256, String)>>().unwrap();
+	stream.finish().unwrap();
+
+	let encoded = fixture(include_str!("../../res/fixtures/nested.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<(U256, (String, Vec<U256>))>().unwrap();
+	stream.pop::<bool>().unwrap();
+	stream.finish().unwrap();
+
+	let encoded = fixture(include_str!("../../res/fixtures/blobs.abi"));
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<Vec<Vec<u8>>>().unwrap();
+	stream.finish().unwrap();
+}
+
+#[test]
+fn strict_padding() {
+	let mut address = [0u8; 32];
+	address[0] = 1;
+	assert!(lenient_stream(&address).pop::<H160>().is_ok());
+	assert_eq!(strict_stream(&address).pop::<H160>().map_err(|err| err.kind()), Err(ErrorKind::InvalidPadding));
+
+	let mut fixed = [0u8; 32];
+	fixed[4] = 1;
+	assert!(lenient_stream(&fixed).pop::<[u8; 4]>().is_ok());
+	assert_eq!(strict_stream(&fixed).pop::<[u8; 4]>().map_err(|err| err.kind()), Err(ErrorKind::InvalidPadding));
+
+	let mut bytes = Vec::new();
+	bytes.extend_from_slice(&util::pad_u32(0x20));
+	bytes.extend_from_slice(&util::pad_u32(2));
+	bytes.extend_from_slice(&[0xab; 32]);
+	assert_eq_core!(lenient_stream(&bytes).pop::<Vec<u8>>().unwrap(), vec![0xab; 2]);
+	assert_eq!(strict_stream(&bytes).pop::<Vec<u8>>().map_err(|err| err.kind()), Err(ErrorKind::InvalidPadding));
+
+	// padding should be present in the strict mode
+	let unpadded = &bytes[..66];
+	assert!(lenient_stream(unpadded).pop::<Vec<u8>>().is_ok());
+	assert_eq!(strict_stream(unpadded).pop::<Vec<u8>>().map_err(|err| err.kind()), Err(ErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn strict_trailing_bytes() {
+	let mut sink = Sink::new(2);
+	sink.push(7u32);
+	sink.push(String::from("seven"));
+	let mut encoded = sink.finalize_panicking();
+	encoded.extend_from_slice(&[0u8; 32]);
+
+	let mut stream = lenient_stream(&encoded);
+	stream.pop::<u32>().unwrap();
+	stream.pop::<String>().unwrap();
+	assert_eq!(stream.finish(), Ok(()));
+
+	let mut stream = strict_stream(&encoded);
+	stream.pop::<u32>().unwrap();
+	stream.pop::<String>().unwrap();
+	assert_eq!(stream.finish().map_err(|err| err.kind()), Err(ErrorKind::TrailingBytes));
+}
+
+#[test]
+fn strict_offsets() {
+	let mut sink = Sink::new(2);
+	sink.push(String::from("one"));
+	sink.push(String::from("two"));
+	let encoded = sink.finalize_panicking();
+
+	// both strings pointing to the same data
+	let mut aliased = encoded.clone();
+	aliased[32..64].copy_from_slice(&util::pad_u32(0x40));
+	let mut stream = lenient_stream(&aliased);
+	assert_eq!(stream.pop::<String>().unwrap(), "one");
+	assert_eq!(stream.pop::<String>().unwrap(), "one");
+	let mut stream = strict_stream(&aliased);
+	assert_eq!(stream.pop::<String>().unwrap(), "one");
+	assert_eq!(stream.pop::<String>().map_err(|err| err.kind()), Err(ErrorKind::InvalidOffset));
+
+	// offsets pointing into the head
+	let mut into_head = encoded.clone();
+	into_head[0..32].copy_from_slice(&util::pad_u32(0));
+	assert_eq!(strict_stream(&into_head).pop::<String>().map_err(|err| err.kind()), Err(ErrorKind::InvalidOffset));
+
+	let mut into_head = encoded.clone();
+	into_head[0..32].copy_from_slice(&util::pad_u32(0x20));
+	let mut stream = strict_stream(&into_head);
+	assert!(stream.pop::<String>().is_ok());
+	assert_eq!(stream.pop::<String>().map_err(|err| err.kind()), Err(ErrorKind::InvalidOffset));
+
+	// gap between the head and the first dynamic value
+	let mut gap = Vec::new();
+	gap.extend_from_slice(&util::pad_u32(0x40));
+	gap.extend_from_slice(&[0u8; 32]);
+	gap.extend_from_slice(&encoded[64..128]);
+	assert_eq!(lenient_stream(&gap).pop::<String>().unwrap(), "one");
+	let mut stream = strict_stream(&gap);
+	assert_eq!(stream.pop::<String>().unwrap(), "one");
+	assert_eq!(stream.finish().map_err(|err| err.kind()), Err(ErrorKind::InvalidOffset));
+}
+
+#[test]
+fn error_context() {
+	let mut sink = Sink::new(2);
+	sink.push(7u32);
+	sink.push(vec![String::from("one"), String::from("two")]);
+	let mut encoded = sink.finalize_panicking();
+	// invalid utf-8 in the second string
+	let last = encoded.len() - 32;
+	encoded[last] = 0xff;
+
+	let mut stream = Stream::new(&encoded);
+	stream.pop::<u32>().unwrap();
+	let err = stream.pop::<Vec<String>>().unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+	assert_eq!(err.expected(), Some("string"));
+	// data of the second string: head (2 words), length and offsets (3 words), first string (2 words)
+	assert_eq!(err.position(), Some(7 * 32));
+	assert_eq!(err.method(), None);
+
+	// the second element of the array is truncated
+	let err = Stream::new(&encoded[..40]).pop::<[u32; 2]>().unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+	assert_eq!(err.expected(), Some("uint32"));
+	assert_eq!(err.position(), Some(32));
+
+	let err = err.in_argument("transfer", "amount");
+	assert_eq!(err.method(), Some("transfer"));
+	assert_eq!(err.argument(), Some("amount"));
+	assert_eq!(
+		format!("{}", err),
+		"unexpected end of the stream decoding uint32 at byte 32 (argument `amount` of `transfer`)"
+	);
+}
+
+#[test]
+fn sink_capacity_mismatch() {
+	let mut sink = Sink::new(2);
+	sink.push(U256::from(1));
+	assert_eq!(sink.finalize(), Err(CapacityMismatch { declared: 64, pushed: 32 }));
+
+	// fixed array takes three words in the head
+	let mut sink = Sink::new(1);
+	sink.push([U256::from(1), U256::from(2), U256::from(3)]);
+	assert_eq!(sink.finalize(), Err(CapacityMismatch { declared: 32, pushed: 96 }));
+}
+
+#[test]
+fn sink_empty() {
+	let mut sized = Sink::new(5);
+	sized.push([U256::from(1), U256::from(2), U256::from(3)]);
+	sized.push(String::from("abc"));
+	sized.push(true);
+	let expected = sized.finalize().unwrap();
+
+	let mut sink = Sink::empty();
+	sink.push([U256::from(1), U256::from(2), U256::from(3)]);
+	sink.push(String::from("abc"));
+	sink.push(true);
+	assert_eq!(sink.finalize().unwrap(), expected);
+
+	assert_eq!(Sink::empty().finalize().unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn sink_with_size() {
+	let strings = vec![String::from("abc"), String::from("defgh")];
+	let size = Sink::size_of(&7u32) + Sink::size_of(&strings);
+
+	let mut sized = Sink::new(2);
+	sized.push(7u32);
+	sized.push(&strings);
+	let expected = sized.finalize().unwrap();
+	assert_eq!(expected.len(), size);
+
+	let mut sink = Sink::with_size(2, size);
+	sink.push(7u32);
+	sink.push(&strings);
+	let result = sink.finalize().unwrap();
+	assert_eq!(result, expected);
+	assert_eq!(result.capacity(), size);
+
+	// offsets don't include the prefix
+	let mut sink = Sink::with_prefix(&[0xcd, 0xcd, 0x77, 0xc0], 2, size);
+	sink.push(7u32);
+	sink.push(&strings);
+	let result = sink.finalize().unwrap();
+	assert_eq!(&result[..4], &[0xcd, 0xcd, 0x77, 0xc0]);
+	assert_eq!(&result[4..], &expected[..]);
+	assert_eq!(result.capacity(), 4 + size);
+}
+
+fn encoded_len<T: AbiType>(value: T) -> usize {
+	let mut sink = Sink::empty();
+	sink.push(value);
+	sink.finalize().unwrap().len()
+}
+
+fn assert_encoded_size<T: AbiType>(value: T) {
+	let size = if T::IS_FIXED { T::HEAD_SIZE } else { 32 + value.encoded_size() };
+	assert_eq!(size, encoded_len(value));
+}
+
+#[test]
+fn encoded_size() {
+	assert_eq!(U256::HEAD_SIZE, 32);
+	assert_eq!(<[U256; 3]>::HEAD_SIZE, 96);
+	assert_eq!(<(u32, [bool; 2])>::HEAD_SIZE, 96);
+	assert_eq!(<(u32, String)>::HEAD_SIZE, 32);
+	assert_eq!(<[String; 2]>::HEAD_SIZE, 32);
+
+	assert_encoded_size(true);
+	assert_encoded_size([U256::from(1), U256::from(2), U256::from(3)]);
+	assert_encoded_size(Vec::<u8>::new());
+	assert_encoded_size(vec![1u8; 33]);
+	assert_encoded_size(String::from("abc"));
+	assert_encoded_size(vec![[1u32, 2u32], [3u32, 4u32]]);
+	assert_encoded_size(vec![vec![1u32], vec![], vec![2u32, 3u32]]);
+	assert_encoded_size((7u32, String::from("abc"), vec![String::from("d"), String::new()]));
+	assert_encoded_size([String::from("abc"), String::from("defghijklmnopqrstuvwxyz0123456789")]);
+}
+
+#[test]
+fn nested_dynamic_roundtrip() {
+	let value = vec![
+		(String::from("one"), vec![1u32, 2u32], [true, false]),
+		(String::from("two"), vec![], [false, true]),
+	];
+	let tail = [String::from("three"), String::new()];
+
+	let mut sink = Sink::new(3);
+	sink.push(value.clone());
+	sink.push(tail.clone());
+	sink.push(5u32);
+	let encoded = sink.finalize().unwrap();
+
+	let mut stream = strict_stream(&encoded);
+	assert_eq!(stream.pop::<Vec<(String, Vec<u32>, [bool; 2])>>().unwrap(), value);
+	assert_eq!(stream.pop::<[String; 2]>().unwrap(), tail);
+	assert_eq!(stream.pop::<u32>().unwrap(), 5);
+	assert!(stream.finish().is_ok());
+
+	let mut sink = Sink::empty();
+	sink.push(value);
+	sink.push(tail);
+	sink.push(5u32);
+	assert_eq_core!(sink.finalize().unwrap(), encoded);
+}
+
+#[test]
+fn encode_borrowed() {
+	let strings = vec![String::from("one"), String::from("two")];
+	let uints = [U256::from(1), U256::from(2)];
+
+	let mut sink = Sink::new(5);
+	sink.push("abc");
+	sink.push(&b"bytes"[..]);
+	sink.push(&uints[..]);
+	sink.push(&strings);
+	sink.push(("four", &uints));
+	let borrowed = sink.finalize().unwrap();
+
+	let mut sink = Sink::new(5);
+	sink.push(String::from("abc"));
+	sink.push(b"bytes".to_vec());
+	sink.push(uints.to_vec());
+	sink.push(strings.clone());
+	sink.push((String::from("four"), uints));
+	assert_eq_core!(borrowed, sink.finalize().unwrap());
+
+	// values are not consumed
+	assert_eq!(strings.len(), 2);
+}
+
+#[test]
+fn decode_borrowed() {
+	let mut sink = Sink::new(2);
+	sink.push(&[0xaau8; 40][..]);
+	sink.push("hi");
+	let mut encoded = sink.finalize().unwrap();
+
+	{
+		let mut stream = strict_stream(&encoded);
+		let data: &[u8] = stream.pop().unwrap();
+		let memo: &str = stream.pop().unwrap();
+		assert_eq!(data, &[0xaau8; 40][..]);
+		assert_eq!(memo, "hi");
+		assert!(stream.finish().is_ok());
+		// borrowed from the payload rather than copied
+		assert_eq!(data.as_ptr(), encoded[3 * 32..].as_ptr());
+	}
+
+	let last = encoded.len() - 32;
+	encoded[last] = 0xff;
+	let mut stream = Stream::new(&encoded);
+	stream.pop::<&[u8]>().unwrap();
+	let err = stream.pop::<&str>().unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+	assert_eq!(err.expected(), Some("string"));
+}
+
+#[test]
+fn fixed_sink() {
+	let value = (7u32, [U256::from(1), U256::from(2)], H160::from([0x11u8; 20]), true, [0xabu8; 4], -1i64);
+
+	let mut sink = Sink::new(7);
+	sink.push(&value);
+	let expected = sink.finalize().unwrap();
+
+	// buffer is not zeroed upfront
+	let mut buffer = [0xffu8; 7 * 32];
+	{
+		let mut sink = FixedSink::new(&mut buffer);
+		sink.push(&value).unwrap();
+		assert_eq!(sink.position(), 7 * 32);
+		assert_eq!(sink.finalize(), Ok(()));
+	}
+	assert_eq!(&buffer[..], &expected[..]);
+
+	let mut stream = strict_stream(&buffer);
+	assert_eq!(stream.pop_fixed::<(u32, [U256; 2], H160, bool, [u8; 4], i64)>().unwrap(), value);
+	assert!(stream.finish().is_ok());
+}
+
+#[test]
+fn fixed_sink_capacity() {
+	let mut buffer = [0u8; 64];
+	let mut sink = FixedSink::new(&mut buffer);
+	assert_eq!(sink.push(&[U256::from(1); 3]), Err(CapacityMismatch { declared: 64, pushed: 96 }));
+	sink.push(&U256::from(1)).unwrap();
+	assert_eq!(sink.finalize(), Err(CapacityMismatch { declared: 64, pushed: 32 }));
+}
+
+#[test]
+fn pop_fixed_error() {
+	let mut payload = [0u8; 64];
+	payload[63] = 2;
+
+	let err = Stream::new(&payload).pop_fixed::<(u32, bool)>().unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::InvalidBool);
+	assert_eq!(err.position(), Some(32));
+	assert_eq!(err.expected(), None);
+
+	let err = Stream::new(&payload[..48]).pop_fixed::<[u32; 2]>().unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+	assert_eq!(err.position(), Some(32));
+}
+
+#[test]
+fn packed() {
+	// abi.encodePacked(int16(-1), bytes1(0x42), uint16(0x03), string("Hello, world!"))
+	let mut sink = PackedSink::new();
+	sink.push(-1i16);
+	sink.push([0x42u8]);
+	sink.push(0x03u16);
+	sink.push("Hello, world!");
+	assert_eq!(sink.finalize(), hex!("ffff42000348656c6c6f2c20776f726c6421").to_vec());
+
+	// abi.encodePacked(address, bool, bytes, int8, uint32[2], uint256[])
+	let mut sink = PackedSink::new();
+	sink.push(H160::from([0x11u8; 20]));
+	sink.push(true);
+	sink.push(&b"ab"[..]);
+	sink.push(-2i8);
+	sink.push([1u32, 2u32]);
+	sink.push(vec![U256::from(3)]);
+	assert_eq!(sink.finalize(), hex!("
+		1111111111111111111111111111111111111111
+		01
+		6162
+		fe
+		0000000000000000000000000000000000000000000000000000000000000001
+		0000000000000000000000000000000000000000000000000000000000000002
+		0000000000000000000000000000000000000000000000000000000000000003
+	").to_vec());
+
+	// abi.encodePacked(uint64, int128, bytes32, bytes3, int256)
+	let mut sink = PackedSink::new();
+	sink.push((0x0102030405060708u64, -1i128, H256::from([0x22u8; 32]), *b"abc", I256::from(-1)));
+	assert_eq!(sink.finalize(), hex!("
+		0102030405060708
+		ffffffffffffffffffffffffffffffff
+		2222222222222222222222222222222222222222222222222222222222222222
+		616263
+		ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff
+	").to_vec());
+}
+
+#[test]
+fn from_log() {
+	assert_eq!(u32::from_log(&0x1234u32.as_log()), Ok(0x1234));
+	assert_eq!(i128::from_log(&(-5i128).as_log()), Ok(-5));
+	assert_eq!(U256::from_log(&U256::from(7).as_log()), Ok(U256::from(7)));
+	assert_eq!(H160::from_log(&H160::from([0x11u8; 20]).as_log()), Ok(H160::from([0x11u8; 20])));
+	assert_eq!(H256::from_log(&H256::from([0x22u8; 32])), Ok(H256::from([0x22u8; 32])));
+
+	assert_eq!(u8::from_log(&0x1234u32.as_log()).unwrap_err().kind(), ErrorKind::InvalidU8);
+}
+
+#[test]
+fn log_stream() {
+	let signature = H256::from([0xaau8; 32]);
+	let topics = [signature, H160::from([0x11u8; 20]).as_log(), 5u64.as_log()];
+	let mut sink = Sink::empty();
+	sink.push(&U256::from(1000));
+	sink.push("memo");
+	let data = sink.finalize().unwrap();
+
+	let mut log = LogStream::new(&signature, &topics, &data).unwrap();
+	assert_eq!(log.pop_indexed::<H160>().unwrap(), H160::from([0x11u8; 20]));
+	assert_eq!(log.pop_indexed::<u64>().unwrap(), 5);
+	assert_eq!(log.pop::<U256>().unwrap(), U256::from(1000));
+	assert_eq!(log.pop::<&str>().unwrap(), "memo");
+	assert!(log.finish().is_ok());
+
+	// indexed arguments left
+	let log = LogStream::new(&signature, &topics, &data).unwrap();
+	assert_eq!(log.finish().unwrap_err().kind(), ErrorKind::InvalidTopicCount);
+
+	// more indexed arguments than topics
+	let mut log = LogStream::new(&signature, &topics[..1], &data).unwrap();
+	assert_eq!(log.pop_indexed::<H160>().unwrap_err().kind(), ErrorKind::InvalidTopicCount);
+
+	// other event or anonymous one
+	assert_eq!(LogStream::new(&H256::zero(), &topics, &data).err().unwrap().kind(), ErrorKind::InvalidEventSignature);
+	assert_eq!(LogStream::new(&signature, &[], &data).err().unwrap().kind(), ErrorKind::InvalidEventSignature);
+}
+
+#[test]
+fn as_log_hashed() {
+	// keccak256("abc")
+	let abc = H256::from(hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"));
+	assert_eq!("abc".as_log(), abc);
+	assert_eq!(String::from("abc").as_log(), abc);
+	assert_eq!(b"abc".to_vec().as_log(), abc);
+
+	// keccak256(hex"0102")
+	assert_eq!(vec![1u8, 2].as_log(), H256::from(hex!("22ae6da6b482f9b1b19b0b897c3fd43884180a1c5ee361e1107a1bc635649dda")));
+
+	// bytesN is the value type
+	assert_eq!([1u8, 2].as_log(), H256::from(hex!("0102000000000000000000000000000000000000000000000000000000000000")));
+	assert_eq!(<[u8; 2]>::from_log(&[1u8, 2].as_log()), Ok([1u8, 2]));
+
+	// items are concatenated without length: keccak256(abi.encode(uint256(1), uint256(2)))
+	let items = H256::from(hex!("e90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0"));
+	assert_eq!(vec![U256::from(1), U256::from(2)].as_log(), items);
+	assert_eq!([U256::from(1), U256::from(2)].as_log(), items);
+	assert_eq!((U256::from(1), U256::from(2)).as_log(), items);
+
+	// strings in the arrays are padded to 32 bytes, without length
+	assert_eq!(
+		vec![String::from("a"), String::from("a")].as_log(),
+		H256::from(hex!("9e5d6103dc5d77dd78d28971ddd2c7aeffea579f33c731852116ba3755d5eee5"))
+	);
+	assert_eq!(
+		(U256::from(1), String::from("a")).as_log(),
+		H256::from(hex!("7dfeb332565b59eb614ab1602ea3784a7ced80b422979bca7c8f4083927ac7e7"))
+	);
+}
+
+#[test]
+fn hashes() {
+	assert_eq!(keccak256(&[]), H256::from(hex!("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")));
+	assert_eq!(keccak256(b"abc"), H256::from(hex!("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45")));
+
+	assert_eq!(selector("transfer(address,uint256)"), 0xa9059cbb);
+	assert_eq!(selector("balanceOf(address)"), 0x70a08231);
+	assert_eq!(
+		event_topic("Transfer(address,address,uint256)"),
+		H256::from(hex!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"))
+	);
+}
+
+#[test]
+#[allow(deprecated)]
+fn sink_legacy_preamble() {
+	// hand-written encoder writing through the deprecated preamble
+	struct Legacy(u32);
+
+	impl AbiEncode for Legacy {
+		fn encode(&self, sink: &mut Sink) {
+			sink.preamble_mut().extend_from_slice(&util::pad_u32(self.0)[..]);
+		}
+
+		fn canonical_name() -> String {
+			"uint32".into()
+		}
+
+		type Canonical = u32;
+
+		const IS_FIXED: bool = true;
+
+		const HEAD_SIZE: usize = 32;
+	}
+
+	let mut expected = Sink::new(3);
+	expected.push(1u32);
+	expected.push(String::from("abc"));
+	expected.push(2u32);
+	let expected = expected.finalize_panicking();
+
+	let mut sink = Sink::new(3);
+	sink.push(Legacy(1));
+	sink.push(String::from("abc"));
+	sink.push(Legacy(2));
+	assert_eq!(sink.finalize_panicking(), expected);
+
+	let mut sink = Sink::empty();
+	sink.push(Legacy(1));
+	sink.push(String::from("abc"));
+	sink.push(Legacy(2));
+	assert_eq!(sink.finalize_panicking(), expected);
+}
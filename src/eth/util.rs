@@ -58,4 +58,18 @@ pub fn pad_i32(value: i32) -> Hash {
 	padded[30] = (value >> 8) as u8;
 	padded[31] = value as u8;
 	padded
-}
\ No newline at end of file
+}
+
+/// Converts u128 to right aligned array of 32 bytes.
+pub fn pad_u128(value: u128) -> Hash {
+	let mut padded = [0u8; 32];
+	padded[16..32].copy_from_slice(&value.to_be_bytes());
+	padded
+}
+
+/// Converts i128 to right aligned, sign extended array of 32 bytes.
+pub fn pad_i128(value: i128) -> Hash {
+	let mut padded = if value < 0 { [0xffu8; 32] } else { [0u8; 32] };
+	padded[16..32].copy_from_slice(&value.to_be_bytes());
+	padded
+}
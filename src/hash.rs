@@ -0,0 +1,22 @@
+//! Fixed-size types for the builds without the `alloc` feature
+//!
+//! `pwasm-std` always links a global allocator (`pwasm-alloc`), so it is a dependency only
+//! with `alloc`. `U256` is the same type as `pwasm_std::types::U256`, while `H160` and `H256`
+//! are constructed as in `pwasm_std::types`, but are distinct types.
+
+pub use uint::U256;
+
+construct_fixed_hash!{
+	/// A 160 bits (20 bytes) hash type.
+	pub struct H160(20);
+}
+
+construct_fixed_hash!{
+	/// A 256-bits (32 bytes) hash type.
+	pub struct H256(32);
+}
+
+impl_fixed_hash_conversions!(H256, H160);
+
+/// Represents an address in ethereum context.
+pub type Address = H160;
@@ -0,0 +1,189 @@
+//! Signed 256-bit integer
+
+use lib::*;
+use types::U256;
+
+/// Signed 256-bit integer (solidity `int256`)
+///
+/// Stored as two's complement representation in the `U256`, the same way it is
+/// encoded in the abi.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct I256(U256);
+
+/// Negates two's complement big-endian bytes in place (wrapping)
+fn negate(bytes: &mut [u8; 32]) {
+	let mut carry = true;
+	for byte in bytes.iter_mut().rev() {
+		*byte = !*byte;
+		if carry {
+			let (value, overflow) = byte.overflowing_add(1);
+			*byte = value;
+			carry = overflow;
+		}
+	}
+}
+
+impl I256 {
+	/// Zero value
+	pub fn zero() -> Self {
+		I256(U256::zero())
+	}
+
+	/// Smallest value that can be represented (-2^255)
+	pub fn min_value() -> Self {
+		let mut bytes = [0u8; 32];
+		bytes[0] = 0x80;
+		I256(U256::from_big_endian(&bytes))
+	}
+
+	/// Largest value that can be represented (2^255 - 1)
+	pub fn max_value() -> Self {
+		let mut bytes = [0xffu8; 32];
+		bytes[0] = 0x7f;
+		I256(U256::from_big_endian(&bytes))
+	}
+
+	/// Instantiate from the raw two's complement representation
+	pub fn from_raw(raw: U256) -> Self {
+		I256(raw)
+	}
+
+	/// Raw two's complement representation
+	pub fn into_raw(self) -> U256 {
+		self.0
+	}
+
+	/// Instantiate from the unsigned value, `None` if it does not fit (>= 2^255)
+	pub fn from_u256(value: U256) -> Option<Self> {
+		let result = I256(value);
+		if result.is_negative() { None } else { Some(result) }
+	}
+
+	/// Convert to the unsigned value, `None` if negative
+	pub fn to_u256(&self) -> Option<U256> {
+		if self.is_negative() { None } else { Some(self.0) }
+	}
+
+	/// Convert to i128, `None` if it does not fit
+	pub fn to_i128(&self) -> Option<i128> {
+		let bytes = self.to_big_endian();
+		let padding = if self.is_negative() { 0xff } else { 0x00 };
+		if !bytes[..16].iter().all(|x| *x == padding) || (bytes[16] & 0x80) != (padding & 0x80) {
+			return None;
+		}
+		let mut low = [0u8; 16];
+		low.copy_from_slice(&bytes[16..]);
+		Some(i128::from_be_bytes(low))
+	}
+
+	/// Convert to i64, `None` if it does not fit
+	pub fn to_i64(&self) -> Option<i64> {
+		self.to_i128().and_then(|value| {
+			if value < i64::min_value() as i128 || value > i64::max_value() as i128 {
+				None
+			} else {
+				Some(value as i64)
+			}
+		})
+	}
+
+	/// Whether value is less than zero
+	pub fn is_negative(&self) -> bool {
+		self.to_big_endian()[0] & 0x80 != 0
+	}
+
+	/// Whether value is zero
+	pub fn is_zero(&self) -> bool {
+		self.0.is_zero()
+	}
+
+	/// Absolute value as unsigned (does not overflow for `min_value`)
+	pub fn abs(&self) -> U256 {
+		let mut bytes = self.to_big_endian();
+		if self.is_negative() {
+			negate(&mut bytes);
+		}
+		U256::from_big_endian(&bytes)
+	}
+
+	/// Wrapping (modular) negation
+	pub fn wrapping_neg(&self) -> Self {
+		let mut bytes = self.to_big_endian();
+		negate(&mut bytes);
+		I256(U256::from_big_endian(&bytes))
+	}
+
+	fn to_big_endian(&self) -> [u8; 32] {
+		let mut bytes = [0u8; 32];
+		self.0.to_big_endian(&mut bytes);
+		bytes
+	}
+}
+
+impl From<i128> for I256 {
+	fn from(value: i128) -> Self {
+		let mut bytes = if value < 0 { [0xffu8; 32] } else { [0u8; 32] };
+		bytes[16..].copy_from_slice(&value.to_be_bytes());
+		I256(U256::from_big_endian(&bytes))
+	}
+}
+
+impl From<i64> for I256 {
+	fn from(value: i64) -> Self {
+		I256::from(value as i128)
+	}
+}
+
+impl From<i32> for I256 {
+	fn from(value: i32) -> Self {
+		I256::from(value as i128)
+	}
+}
+
+impl cmp::PartialOrd for I256 {
+	fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl cmp::Ord for I256 {
+	fn cmp(&self, other: &Self) -> cmp::Ordering {
+		match (self.is_negative(), other.is_negative()) {
+			(true, false) => cmp::Ordering::Less,
+			(false, true) => cmp::Ordering::Greater,
+			// two's complement values of the same sign compare as unsigned
+			_ => self.0.cmp(&other.0),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::I256;
+	use types::U256;
+
+	#[test]
+	fn conversions() {
+		assert_eq!(I256::from(-1i64).into_raw(), U256::from_big_endian(&[0xffu8; 32]));
+		assert_eq!(I256::from(-1i64).to_i64(), Some(-1));
+		assert_eq!(I256::from(i128::min_value()).to_i128(), Some(i128::min_value()));
+		assert_eq!(I256::from(i128::min_value()).to_i64(), None);
+		assert_eq!(I256::max_value().to_i128(), None);
+		assert_eq!(I256::min_value().to_i128(), None);
+		assert_eq!(I256::from(1i128 << 64).to_i64(), None);
+		assert_eq!(I256::from(-5i32).abs(), U256::from(5));
+		assert_eq!(I256::min_value().abs(), U256::from(1) << 255);
+		assert_eq!(I256::from_u256(U256::from(1) << 255), None);
+		assert_eq!(I256::from_u256(U256::from(7)), Some(I256::from(7i64)));
+		assert_eq!(I256::from(-7i64).to_u256(), None);
+		assert_eq!(I256::from(7i64).wrapping_neg(), I256::from(-7i64));
+	}
+
+	#[test]
+	fn ordering() {
+		assert!(I256::min_value() < I256::from(-1i64));
+		assert!(I256::from(-1i64) < I256::zero());
+		assert!(I256::zero() < I256::from(1i64));
+		assert!(I256::from(1i64) < I256::max_value());
+	}
+}
@@ -1,28 +1,44 @@
 //! WASM ABI Tools
 
 #![cfg_attr(not(feature="std"), no_std)]
-#![cfg_attr(not(feature="std"), feature(alloc))]
+#![cfg_attr(all(feature="alloc", not(feature="std")), feature(alloc))]
 #![warn(missing_docs)]
 #![cfg_attr(feature="strict", deny(unused))]
 
 extern crate byteorder;
+#[cfg(feature="alloc")]
 extern crate pwasm_std;
+extern crate tiny_keccak;
+#[cfg(not(feature="alloc"))]
+extern crate uint;
+#[cfg(not(feature="alloc"))]
+#[macro_use]
+extern crate fixed_hash;
 
 #[cfg(test)]
 #[cfg_attr(all(test, feature = "std"), macro_use)]
 extern crate hex_literal;
 
-#[cfg(not(feature="std"))]
+#[cfg(all(feature="alloc", not(feature="std")))]
 #[allow(unused)]
 #[macro_use] extern crate alloc;
 
 pub mod eth;
+mod i256;
+#[cfg(not(feature="alloc"))]
+mod hash;
 
 /// Custom types which AbiType supports
 pub mod types {
+	#[cfg(feature = "alloc")]
 	pub use pwasm_std::Vec;
+	#[cfg(feature = "alloc")]
 	pub use pwasm_std::String;
+	#[cfg(feature = "alloc")]
 	pub use pwasm_std::types::*;
+	#[cfg(not(feature = "alloc"))]
+	pub use hash::*;
+	pub use i256::I256;
 }
 
 mod lib {
@@ -49,21 +65,21 @@ mod lib {
 
 	#[cfg(feature = "std")]
 	pub use std::borrow::{Cow, ToOwned};
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(feature = "alloc", not(feature = "std")))]
 	pub use alloc::borrow::{Cow, ToOwned};
 
 	#[cfg(feature = "std")]
 	pub use std::string::String;
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(feature = "alloc", not(feature = "std")))]
 	pub use alloc::string::{String, ToString};
 
 	#[cfg(feature = "std")]
 	pub use std::vec::Vec;
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(feature = "alloc", not(feature = "std")))]
 	pub use alloc::vec::Vec;
 
 	#[cfg(feature = "std")]
 	pub use std::boxed::Box;
-	#[cfg(not(feature = "std"))]
+	#[cfg(all(feature = "alloc", not(feature = "std")))]
 	pub use alloc::boxed::Box;
 }
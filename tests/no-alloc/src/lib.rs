@@ -0,0 +1,64 @@
+//! Contract code using only the fixed-size types, built without the `alloc` feature
+//! of pwasm-abi, so neither `alloc` nor `pwasm-std` (which links the `pwasm-alloc` global
+//! allocator) is linked. Only the tests use `std`.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate pwasm_abi;
+
+use pwasm_abi::eth::{CapacityMismatch, Error, FixedSink, Stream};
+use pwasm_abi::types::{Address, U256};
+
+/// Decodes the arguments of `transfer(address,uint256)` (the payload past the selector)
+pub fn decode_transfer(payload: &[u8]) -> Result<(Address, U256), Error> {
+	let mut stream = Stream::new(payload);
+	let to = stream.pop::<Address>()?;
+	let amount = stream.pop_fixed::<U256>()?;
+	stream.finish()?;
+	Ok((to, amount))
+}
+
+/// Encodes the balance and the result of the transfer into the two words of `target`
+pub fn encode_result(balance: U256, success: bool, target: &mut [u8; 64]) -> Result<(), CapacityMismatch> {
+	let mut sink = FixedSink::new(&mut target[..]);
+	sink.push(&balance)?;
+	sink.push(&success)?;
+	sink.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+	use pwasm_abi::eth::ErrorKind;
+	use pwasm_abi::types::{Address, U256};
+	use super::{decode_transfer, encode_result};
+
+	#[test]
+	fn transfer() {
+		let mut payload = [0u8; 64];
+		payload[12..32].copy_from_slice(&[0x11; 20]);
+		payload[62] = 0x03;
+		payload[63] = 0xe8;
+
+		assert_eq!(decode_transfer(&payload), Ok((Address::from([0x11; 20]), U256::from(1000))));
+	}
+
+	#[test]
+	fn transfer_truncated() {
+		let err = decode_transfer(&[0u8; 48]).unwrap_err();
+
+		assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+		assert_eq!(err.position(), Some(32));
+	}
+
+	#[test]
+	fn result() {
+		let mut target = [0xffu8; 64];
+		encode_result(U256::from(1000), true, &mut target).unwrap();
+
+		let mut expected = [0u8; 64];
+		expected[30] = 0x03;
+		expected[31] = 0xe8;
+		expected[63] = 0x01;
+		assert_eq!(&target[..], &expected[..]);
+	}
+}
@@ -1,35 +1,74 @@
 #![allow(dead_code)]
 
 use pwasm_abi::eth::EndpointInterface;
+use pwasm_abi::types::{U256, Address};
 use pwasm_abi_derive::eth_abi;
-
+use pwasm_test::{ext_get, ext_reset, Endpoint};
 
 #[eth_abi(DoubleArrayEndpoint, DoubleArrayClient)]
 pub trait DoubleArrayContract {
 	fn double_array(&mut self, v: [u8; 16]);
+	fn fixed(&mut self, amounts: [U256; 3], owners: [Address; 2], names: [String; 2]);
 }
 
-const PAYLOAD_SAMPLE_1: &[u8] = &[
+pub const PAYLOAD_SAMPLE_1: &[u8] = &[
 	0x71, 0x3d, 0x4b, 0x80,
 	0x12, 0x24, 0x36, 0x48, 0x60, 0x72, 0x84, 0x96, 0x07, 0x14, 0x21, 0x28, 0x35, 0x42, 0x49, 0x56,
 	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 ];
 
-#[test]
-fn bytes16() {
-	#[derive(Default)]
-	pub struct Instance {
-		pub v1: [u8; 8],
-		pub v2: [u8; 8],
+pub const PAYLOAD_SAMPLE_2: &[u8] = &[
+	0x99, 0x09, 0xe9, 0xd7,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0x22, 0x22, 0x22,
+	0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+	0x61, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+	0x62, 0x63, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[derive(Default)]
+pub struct Instance {
+	pub v1: [u8; 8],
+	pub v2: [u8; 8],
+	pub amounts: [U256; 3],
+	pub owners: [Address; 2],
+	pub names: [String; 2],
+}
+
+impl DoubleArrayContract for Instance {
+	fn double_array(&mut self, v: [u8; 16]) {
+		self.v1.copy_from_slice(&v[0..8]);
+		self.v2.copy_from_slice(&v[8..16]);
 	}
 
-	impl DoubleArrayContract for Instance {
-		fn double_array(&mut self, v: [u8; 16]) {
-			self.v1.copy_from_slice(&v[0..8]);
-			self.v2.copy_from_slice(&v[8..16]);
-		}
+	fn fixed(&mut self, amounts: [U256; 3], owners: [Address; 2], names: [String; 2]) {
+		self.amounts = amounts;
+		self.owners = owners;
+		self.names = names;
 	}
+}
 
+#[test]
+fn bytes16() {
 	let mut endpoint = DoubleArrayEndpoint::new(Instance::default());
 
 	endpoint.dispatch(PAYLOAD_SAMPLE_1);
@@ -37,3 +76,26 @@ fn bytes16() {
 	assert_eq!(endpoint.inner.v1, [0x12, 0x24, 0x36, 0x48, 0x60, 0x72, 0x84, 0x96]);
 	assert_eq!(endpoint.inner.v2, [0x07, 0x14, 0x21, 0x28, 0x35, 0x42, 0x49, 0x56]);
 }
+
+#[test]
+fn fixed_arrays() {
+	let mut endpoint = DoubleArrayEndpoint::new(Instance::default());
+
+	endpoint.dispatch(PAYLOAD_SAMPLE_2);
+
+	assert_eq!(endpoint.inner.amounts, [U256::from(1), U256::from(2), U256::from(3)]);
+	assert_eq!(endpoint.inner.owners, [Address::from([0x11u8; 20]), Address::from([0x22u8; 20])]);
+	assert_eq!(endpoint.inner.names, [String::from("a"), String::from("bc")]);
+}
+
+#[test]
+fn fixed_arrays_call() {
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::ok()));
+	let mut client = DoubleArrayClient::new(Address::zero());
+	client.fixed(
+		[U256::from(1), U256::from(2), U256::from(3)],
+		[Address::from([0x11u8; 20]), Address::from([0x22u8; 20])],
+		[String::from("a"), String::from("bc")],
+	);
+	assert_eq!(ext_get().calls()[0].input.as_ref(), PAYLOAD_SAMPLE_2);
+}
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::EndpointInterface;
+use pwasm_abi::types::{U256, Address};
+use pwasm_abi_derive::eth_abi;
+use pwasm_test::{ext_get, ext_reset, Endpoint};
+
+#[eth_abi(BorrowedEndpoint, BorrowedClient)]
+pub trait BorrowedContract {
+	fn forward(&mut self, data: &[u8], memo: &str) -> U256;
+}
+
+// forward(bytes,string)
+pub const PAYLOAD_FORWARD: &[u8] = &[
+	0x5d, 0x03, 0x0f, 0x73,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa0,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x28,
+	0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+	0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa,
+	0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+	0x68, 0x69, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+#[derive(Default)]
+pub struct Instance {
+	forwarded: Vec<(Vec<u8>, String)>,
+}
+
+impl BorrowedContract for Instance {
+	fn forward(&mut self, data: &[u8], memo: &str) -> U256 {
+		self.forwarded.push((data.to_vec(), memo.to_owned()));
+		U256::from(data.len() + memo.len())
+	}
+}
+
+#[test]
+fn forward() {
+	let mut endpoint = BorrowedEndpoint::new(Instance::default());
+	let result = endpoint.dispatch(PAYLOAD_FORWARD);
+
+	assert_eq!(endpoint.inner.forwarded, vec![(vec![0xaa; 40], String::from("hi"))]);
+	assert_eq!(&result[..], &[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x2a,
+	][..]);
+}
+
+#[test]
+fn forward_call() {
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::ok()));
+	let mut client = BorrowedClient::new(Address::zero());
+	client.forward(&[0xaa; 40], "hi");
+	assert_eq!(ext_get().calls()[0].input.as_ref(), PAYLOAD_FORWARD);
+}
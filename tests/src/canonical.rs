@@ -0,0 +1,131 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::{EndpointInterface, event_topic};
+use pwasm_abi::types::{U256, H160, H256, Address};
+use pwasm_abi::types::H160 as Recipient;
+use pwasm_abi_derive::{eth_abi, selector, topic};
+use pwasm_test::{ext_get, ext_reset};
+
+type Amount = U256;
+type Account = H160;
+
+#[eth_abi(CanonicalEndpoint, CanonicalClient)]
+pub trait CanonicalContract {
+	fn commit(&mut self, hash: H256);
+	fn pay(&mut self, #[abi_type = "address"] to: Account, #[abi_type = "uint256"] amount: Amount);
+
+	#[event]
+	fn Paid(&mut self, #[abi_type = "address"] indexed_to: Address, #[abi_type = "uint256"] amount: Amount);
+}
+
+// commit(bytes32)
+pub const PAYLOAD_COMMIT: &[u8] = &[
+	0xf1, 0x4f, 0xcb, 0xc8,
+	0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab,
+	0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab, 0xab,
+];
+
+// pay(address,uint256)
+pub const PAYLOAD_PAY: &[u8] = &[
+	0xc4, 0x07, 0x68, 0x76,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8,
+];
+
+#[derive(Default)]
+pub struct Instance {
+	hash: H256,
+	payments: Vec<(Account, Amount)>,
+}
+
+impl CanonicalContract for Instance {
+	fn commit(&mut self, hash: H256) {
+		self.hash = hash;
+	}
+
+	fn pay(&mut self, to: Account, amount: Amount) {
+		self.payments.push((to, amount));
+		self.Paid(to, amount);
+	}
+}
+
+#[test]
+fn commit() {
+	let mut endpoint = CanonicalEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_COMMIT);
+
+	assert_eq!(endpoint.inner.hash, H256::from([0xabu8; 32]));
+}
+
+#[test]
+fn pay() {
+	ext_reset(|e| e);
+	let mut endpoint = CanonicalEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_PAY);
+
+	assert_eq!(endpoint.inner.payments, vec![(H160::from([0x11u8; 20]), U256::from(1000))]);
+
+	let logs = ext_get().logs().to_vec();
+	assert_eq!(logs.len(), 1);
+	// keccak256("Paid(address,uint256)")
+	assert_eq!(logs[0].topics[0], H256::from([
+		0x73, 0x7c, 0x69, 0x22, 0x5d, 0x64, 0x7e, 0x59, 0x94, 0xea, 0xb1, 0xa6, 0xc3, 0x01, 0xbf, 0x6d,
+		0x92, 0x32, 0xbe, 0xb2, 0x75, 0x9a, 0xe1, 0xe2, 0x7a, 0x89, 0x66, 0xb4, 0x73, 0x2b, 0xc4, 0x89,
+	]));
+	assert_eq!(logs[0].topics[0], event_topic("Paid(address,uint256)"));
+	assert_eq!(logs[0].topics[0], topic!("Paid(address,uint256)"));
+}
+
+/// Same name as the struct in `structs`, with other fields
+mod refunds {
+	use pwasm_abi::types::U256;
+	use pwasm_abi_derive::AbiType;
+
+	#[derive(AbiType, Debug, PartialEq)]
+	pub struct Transfer {
+		pub amount: U256,
+		pub partial: bool,
+	}
+}
+
+#[eth_abi(AliasedEndpoint)]
+pub trait AliasedContract {
+	fn pay(&mut self, #[abi_type = "address"] to: Recipient, #[abi_type = "uint256"] amount: Amount);
+	fn refund(&mut self, #[abi_type = "(uint256,bool)"] transfer: refunds::Transfer);
+}
+
+#[derive(Default)]
+pub struct AliasedInstance {
+	payments: Vec<(Recipient, Amount)>,
+	refunds: Vec<refunds::Transfer>,
+}
+
+impl AliasedContract for AliasedInstance {
+	fn pay(&mut self, to: Recipient, amount: Amount) {
+		self.payments.push((to, amount));
+	}
+
+	fn refund(&mut self, transfer: refunds::Transfer) {
+		self.refunds.push(transfer);
+	}
+}
+
+#[test]
+fn aliased_types() {
+	let mut endpoint = AliasedEndpoint::new(AliasedInstance::default());
+
+	let mut payload = selector!("pay(address,uint256)").to_be_bytes().to_vec();
+	payload.extend_from_slice(&PAYLOAD_PAY[4..]);
+	endpoint.dispatch(&payload);
+
+	let mut payload = selector!("refund((uint256,bool))").to_be_bytes().to_vec();
+	payload.extend_from_slice(&[0u8; 64]);
+	payload[35] = 0x05;
+	payload[67] = 0x01;
+	endpoint.dispatch(&payload);
+
+	assert_eq!(endpoint.inner.payments, vec![(H160::from([0x11u8; 20]), U256::from(1000))]);
+	assert_eq!(endpoint.inner.refunds, vec![refunds::Transfer { amount: U256::from(5), partial: true }]);
+}
@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::{AsLog, EndpointInterface, FromLog, PackedSink};
+use pwasm_abi_derive::{eth_abi, AbiType};
+
+#[derive(AbiType, Clone, Copy, Debug, PartialEq)]
+pub enum State {
+	Created,
+	Locked,
+	Inactive = 5,
+}
+
+#[eth_abi(EnumsEndpoint, EnumsClient)]
+pub trait EnumsContract {
+	fn set_state(&mut self, #[abi_type = "uint8"] state: State);
+	#[abi_returns = "uint8"]
+	fn state(&mut self) -> State;
+	fn set_states(&mut self, #[abi_type = "uint8[]"] states: Vec<State>);
+}
+
+// set_state(uint8)
+pub const PAYLOAD_SET_STATE: &[u8] = &[
+	0x09, 0xa5, 0x9c, 0xaa,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+];
+
+// set_state(uint8), 2 is not a discriminant of any variant
+pub const PAYLOAD_SET_STATE_INVALID: &[u8] = &[
+	0x09, 0xa5, 0x9c, 0xaa,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+];
+
+// state()
+pub const PAYLOAD_STATE: &[u8] = &[0xc1, 0x9d, 0x93, 0xfb];
+
+// set_states(uint8[])
+pub const PAYLOAD_SET_STATES: &[u8] = &[
+	0xc8, 0x4d, 0xae, 0x18,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+pub struct Instance {
+	states: Vec<State>,
+}
+
+impl Default for Instance {
+	fn default() -> Self {
+		Instance { states: vec![State::Created] }
+	}
+}
+
+impl EnumsContract for Instance {
+	fn set_state(&mut self, state: State) {
+		self.states = vec![state];
+	}
+
+	fn state(&mut self) -> State {
+		*self.states.last().expect("Instance always has a state")
+	}
+
+	fn set_states(&mut self, states: Vec<State>) {
+		self.states = states;
+	}
+}
+
+#[test]
+fn set_state() {
+	let mut endpoint = EnumsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_SET_STATE);
+
+	assert_eq!(endpoint.inner.states, vec![State::Locked]);
+}
+
+#[test]
+#[should_panic(expected = "kind: InvalidEnumValue, position: Some(0), expected: Some(\"uint8\"), method: Some(\"set_state\"), argument: Some(\"state\")")]
+fn set_state_invalid() {
+	let mut endpoint = EnumsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_SET_STATE_INVALID);
+}
+
+#[test]
+fn state() {
+	let mut endpoint = EnumsEndpoint::new(Instance { states: vec![State::Inactive] });
+	let result = endpoint.dispatch(PAYLOAD_STATE);
+
+	assert_eq!(&result[..], &[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05,
+	][..]);
+}
+
+#[test]
+fn set_states() {
+	let mut endpoint = EnumsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_SET_STATES);
+
+	assert_eq!(endpoint.inner.states, vec![State::Inactive, State::Created]);
+}
+
+#[test]
+fn packed() {
+	let mut sink = PackedSink::new();
+	sink.push(State::Inactive);
+	sink.push(vec![State::Locked]);
+
+	// uint8 takes a single byte, while the items of the arrays are padded
+	assert_eq!(&sink.finalize()[..], &[
+		0x05,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+	][..]);
+}
+
+#[test]
+fn as_log() {
+	let topic = State::Inactive.as_log();
+
+	assert_eq!(topic, 5u8.as_log());
+	assert_eq!(State::from_log(&topic), Ok(State::Inactive));
+}
@@ -0,0 +1,85 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::EndpointInterface;
+use pwasm_abi_derive::eth_abi;
+use pwasm_abi::types::I256;
+
+#[eth_abi(IntegersEndpoint, IntegersClient)]
+pub trait IntegersContract {
+	fn narrow(&mut self, a: u8, b: i16, c: u128);
+	fn decimals(&mut self) -> u8;
+	fn negate(&mut self, v: I256) -> I256;
+}
+
+pub const PAYLOAD_NARROW: &[u8] = &[
+	0xa9, 0xd1, 0xaf, 0x6a,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12,
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+pub const PAYLOAD_DECIMALS: &[u8] = &[0x31, 0x3c, 0xe5, 0x67];
+
+pub const PAYLOAD_NEGATE: &[u8] = &[
+	0x25, 0xb8, 0x32, 0xd9,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x45,
+];
+
+#[derive(Default)]
+pub struct Instance {
+	a: u8,
+	b: i16,
+	c: u128,
+}
+
+impl IntegersContract for Instance {
+	fn narrow(&mut self, a: u8, b: i16, c: u128) {
+		self.a = a;
+		self.b = b;
+		self.c = c;
+	}
+
+	fn decimals(&mut self) -> u8 {
+		18
+	}
+
+	fn negate(&mut self, v: I256) -> I256 {
+		v.wrapping_neg()
+	}
+}
+
+#[test]
+fn narrow() {
+	let mut endpoint = IntegersEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_NARROW);
+
+	assert_eq!(endpoint.inner.a, 18);
+	assert_eq!(endpoint.inner.b, -2);
+	assert_eq!(endpoint.inner.c, 1u128 << 127);
+}
+
+#[test]
+fn decimals() {
+	let mut endpoint = IntegersEndpoint::new(Instance::default());
+	let result = endpoint.dispatch(PAYLOAD_DECIMALS);
+
+	assert_eq!(&result[..], &[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12,
+	][..]);
+}
+
+#[test]
+fn negate() {
+	let mut endpoint = IntegersEndpoint::new(Instance::default());
+	let result = endpoint.dispatch(PAYLOAD_NEGATE);
+
+	assert_eq!(&result[..], &[
+		0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+		0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xbb,
+	][..]);
+}
@@ -17,3 +17,10 @@ mod trivia;
 mod payable;
 mod multiple_return;
 mod general;
+mod integers;
+mod structs;
+mod enums;
+mod newtypes;
+mod canonical;
+mod borrowed;
+mod selectors;
@@ -1,7 +1,11 @@
 #![allow(dead_code)]
 
 use pwasm_abi::eth::EndpointInterface;
-use pwasm_abi_derive::eth_abi;
+use pwasm_abi::types::{H160, U256};
+use pwasm_abi_derive::{eth_abi, selector};
+use pwasm_test::{ext_reset, Endpoint};
+
+type Address = H160;
 
 #[eth_abi(TupleReturnEndpoint, TupleReturnClient)]
 pub trait TupleReturnContract {
@@ -28,13 +32,13 @@ fn multiple_return() {
 
 	let mut endpoint = TupleReturnEndpoint::new(Instance);
 
-	let res2 = endpoint.dispatch(&[0xa6, 0x37, 0xe6, 0x9c]);
+	let res2 = endpoint.dispatch(&selector!("ret2()").to_be_bytes());
 	assert_eq!(&res2[..], &[
 		0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2
 	][..]);
 
-	let res6 = endpoint.dispatch(&[0x8f, 0x34, 0x96, 0x73]);
+	let res6 = endpoint.dispatch(&selector!("ret6()").to_be_bytes());
 	assert_eq!(&res6[..], &[
  		0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
@@ -44,11 +48,57 @@ fn multiple_return() {
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6
 	][..]);
 
-	let res_var = endpoint.dispatch(&[0x50, 0x47, 0xe7, 0x52]);
+	let res_var = endpoint.dispatch(&selector!("ret_var()").to_be_bytes());
 	assert_eq!(&res_var[..], &[
 		0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64,
 		0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
 		1, 2, 3, 5, 7, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
 	][..]);
-}
\ No newline at end of file
+}
+
+// ret_var() output
+const RESULT_RET_VAR: &[u8] = &[
+	0u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64,
+	0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6,
+	1, 2, 3, 5, 7, 11, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+];
+
+#[test]
+fn ret_var_call() {
+	pub struct Instance;
+
+	impl TupleReturnContract for Instance {
+		fn ret2(&mut self) -> (u64, u64) {
+			(2, 3)
+		}
+		fn ret6(&mut self) -> (u64, u64, u64, u64, u64, u64) {
+			(1, 2, 3, 4, 5, 6)
+		}
+		fn ret_var(&mut self) -> (u64, Vec<u8>) {
+			(7, vec![0xab; 100])
+		}
+	}
+
+	// output of the endpoint is decoded by the client
+	let mut endpoint = TupleReturnEndpoint::new(Instance);
+
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::result(endpoint.dispatch(&selector!("ret_var()").to_be_bytes()))));
+	assert_eq!(TupleReturnClient::new(Address::zero()).ret_var(), (7, vec![0xab; 100]));
+
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::result(endpoint.dispatch(&selector!("ret6()").to_be_bytes()))));
+	assert_eq!(TupleReturnClient::new(Address::zero()).ret6(), (1, 2, 3, 4, 5, 6));
+
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::result(RESULT_RET_VAR.to_vec())));
+	assert_eq!(TupleReturnClient::new(Address::zero()).ret_var(), (6, vec![1, 2, 3, 5, 7, 11]));
+}
+
+#[test]
+#[should_panic(expected = "failed decode call output")]
+fn ret_var_call_truncated() {
+	ext_reset(|e| e.endpoint(Address::zero(), Endpoint::result(RESULT_RET_VAR.to_vec())));
+
+	// output does not fit the buffer
+	TupleReturnClient::new(Address::zero()).result_capacity(64).ret_var();
+}
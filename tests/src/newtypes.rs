@@ -0,0 +1,109 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::{EndpointInterface, LogStream, ErrorKind};
+use pwasm_abi::types::{U256, H256, Address};
+use pwasm_abi_derive::{eth_abi, AbiType};
+use pwasm_test::{ext_get, ext_reset};
+
+#[derive(AbiType, Clone, Copy, Debug, PartialEq)]
+pub struct TokenAmount(U256);
+
+#[derive(AbiType, Clone, Copy, Debug, PartialEq)]
+pub struct Owner(Address);
+
+#[eth_abi(NewtypesEndpoint, NewtypesClient)]
+pub trait NewtypesContract {
+	fn transfer(&mut self, #[abi_type = "address"] to: Owner, #[abi_type = "uint256"] amount: TokenAmount);
+	#[abi_returns = "uint256"]
+	fn balance_of(&mut self, #[abi_type = "address"] owner: Owner) -> TokenAmount;
+
+	#[event]
+	fn Transferred(&mut self, #[abi_type = "address"] indexed_to: Owner, #[abi_type = "uint256"] amount: TokenAmount);
+}
+
+// transfer(address,uint256)
+pub const PAYLOAD_TRANSFER: &[u8] = &[
+	0xa9, 0x05, 0x9c, 0xbb,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8,
+];
+
+// balance_of(address)
+pub const PAYLOAD_BALANCE_OF: &[u8] = &[
+	0xb1, 0x44, 0xad, 0xfb,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+];
+
+#[derive(Default)]
+pub struct Instance {
+	balances: Vec<(Owner, TokenAmount)>,
+}
+
+impl NewtypesContract for Instance {
+	fn transfer(&mut self, to: Owner, amount: TokenAmount) {
+		self.balances.push((to, amount));
+		self.Transferred(to, amount);
+	}
+
+	fn balance_of(&mut self, owner: Owner) -> TokenAmount {
+		self.balances
+			.iter()
+			.find(|&&(ref balance_owner, _)| *balance_owner == owner)
+			.map(|&(_, amount)| amount)
+			.unwrap_or(TokenAmount(U256::zero()))
+	}
+}
+
+#[test]
+fn transfer() {
+	ext_reset(|e| e);
+	let mut endpoint = NewtypesEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_TRANSFER);
+
+	assert_eq!(endpoint.inner.balances, vec![(Owner(Address::from([0x11u8; 20])), TokenAmount(U256::from(1000)))]);
+
+	let logs = ext_get().logs().to_vec();
+	assert_eq!(logs.len(), 1);
+	// keccak256("Transferred(address,uint256)")
+	assert_eq!(logs[0].topics[0], H256::from([
+		0xe6, 0xd8, 0x58, 0xf1, 0x4d, 0x75, 0x54, 0x46, 0x64, 0x8a, 0x6e, 0x0c, 0x8a, 0xb8, 0xb5, 0xa0,
+		0xf5, 0x8c, 0xcc, 0x79, 0x20, 0xd4, 0xc9, 0x10, 0xb0, 0x45, 0x4e, 0x4d, 0xcd, 0x86, 0x9a, 0xf0,
+	]));
+	assert_eq!(&logs[0].topics[1][..], &PAYLOAD_TRANSFER[4..36]);
+	assert_eq!(&logs[0].data[..], &PAYLOAD_TRANSFER[36..]);
+}
+
+#[test]
+fn decode_transferred() {
+	ext_reset(|e| e);
+	let mut endpoint = NewtypesEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_TRANSFER);
+
+	let logs = ext_get().logs().to_vec();
+	let signature = logs[0].topics[0];
+
+	let mut log = LogStream::new(&signature, &logs[0].topics, &logs[0].data).unwrap();
+	assert_eq!(log.pop_indexed::<Owner>().unwrap(), Owner(Address::from([0x11u8; 20])));
+	assert_eq!(log.pop::<TokenAmount>().unwrap(), TokenAmount(U256::from(1000)));
+	assert!(log.finish().is_ok());
+
+	// topic0 of the other event
+	let err = LogStream::new(&H256::zero(), &logs[0].topics, &logs[0].data).err().unwrap();
+	assert_eq!(err.kind(), ErrorKind::InvalidEventSignature);
+}
+
+#[test]
+fn balance_of() {
+	let mut endpoint = NewtypesEndpoint::new(Instance {
+		balances: vec![(Owner(Address::from([0x11u8; 20])), TokenAmount(U256::from(1000)))],
+	});
+	let result = endpoint.dispatch(PAYLOAD_BALANCE_OF);
+
+	assert_eq!(&result[..], &[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8,
+	][..]);
+}
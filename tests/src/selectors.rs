@@ -0,0 +1,46 @@
+use pwasm_abi::eth::selector;
+use pwasm_abi_derive::selector;
+
+use {arrays, borrowed, canonical, enums, general, integers, newtypes, payable, strings, structs};
+
+/// Payloads dispatched by the other tests, with the signature their selector is computed from
+macro_rules! payloads {
+	($($payload:expr => $signature:tt,)*) => {
+		&[$(($payload, $signature, selector!($signature)),)*]
+	}
+}
+
+const PAYLOADS: &[(&[u8], &str, u32)] = payloads! {
+	arrays::PAYLOAD_SAMPLE_1 => "double_array(bytes16)",
+	arrays::PAYLOAD_SAMPLE_2 => "fixed(uint256[3],address[2],string[2])",
+	borrowed::PAYLOAD_FORWARD => "forward(bytes,string)",
+	canonical::PAYLOAD_COMMIT => "commit(bytes32)",
+	canonical::PAYLOAD_PAY => "pay(address,uint256)",
+	enums::PAYLOAD_SET_STATE => "set_state(uint8)",
+	enums::PAYLOAD_SET_STATE_INVALID => "set_state(uint8)",
+	enums::PAYLOAD_STATE => "state()",
+	enums::PAYLOAD_SET_STATES => "set_states(uint8[])",
+	general::PAYLOAD_SAMPLE_1 => "baz(uint32,bool)",
+	general::PAYLOAD_SAMPLE_2 => "sam(bytes,bool,uint256[])",
+	general::PAYLOAD_SAMPLE_3 => "boo(uint32)",
+	integers::PAYLOAD_NARROW => "narrow(uint8,int16,uint128)",
+	integers::PAYLOAD_DECIMALS => "decimals()",
+	integers::PAYLOAD_NEGATE => "negate(int256)",
+	newtypes::PAYLOAD_TRANSFER => "transfer(address,uint256)",
+	newtypes::PAYLOAD_BALANCE_OF => "balance_of(address)",
+	payable::PAYLOAD_BAZ => "baz(uint32,bool)",
+	payable::PAYLOAD_BOO => "boo(uint32)",
+	strings::PAYLOAD_SAMPLE_1 => "string(string)",
+	structs::PAYLOAD_TRANSFER => "transfer((address,uint256))",
+	structs::PAYLOAD_TRANSFERS => "transfers((address,uint256)[])",
+	structs::PAYLOAD_NAMED_OF => "named_of(uint256)",
+	structs::PAYLOAD_LAST_TRANSFER => "last_transfer()",
+};
+
+#[test]
+fn payload_selectors() {
+	for &(payload, signature, compiled) in PAYLOADS {
+		assert_eq!(&payload[0..4], &selector(signature).to_be_bytes(), "{}", signature);
+		assert_eq!(&payload[0..4], &compiled.to_be_bytes(), "{}", signature);
+	}
+}
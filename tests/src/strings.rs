@@ -1,15 +1,19 @@
 #![allow(dead_code)]
 
 use pwasm_abi::eth::EndpointInterface;
+use pwasm_abi::types::H256;
 use pwasm_abi_derive::eth_abi;
-
+use pwasm_test::{ext_get, ext_reset};
 
 #[eth_abi(StringsEndpoint, StringsClient)]
 pub trait StringsContract {
 	fn string(&mut self, v: String);
+
+	#[event]
+	fn Labeled(&mut self, indexed_label: String, indexed_data: Vec<u8>, memo: String);
 }
 
-const PAYLOAD_SAMPLE_1: &[u8] = &[
+pub const PAYLOAD_SAMPLE_1: &[u8] = &[
 	0x3F, 0xCF, 0x74, 0xC6,
 	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
 	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
@@ -40,3 +44,37 @@ fn strings() {
 	let test_string = String::from("Ash nazg thrakatulûk agh burzum-ishi krimpatul");
 	assert_eq!(endpoint.inner.s1, test_string);
 }
+
+#[test]
+fn indexed_strings() {
+	#[derive(Default)]
+	pub struct Instance;
+
+	impl StringsContract for Instance {
+		fn string(&mut self, s: String) {
+			self.Labeled(s, vec![0x01, 0x02], "memo".to_owned());
+		}
+	}
+
+	ext_reset(|e| e);
+	let mut endpoint = StringsEndpoint::new(Instance::default());
+	endpoint.inner.string("abc".to_owned());
+
+	let logs = ext_get().logs().to_vec();
+	assert_eq!(logs.len(), 1);
+	// keccak256("Labeled(string,bytes,string)")
+	assert_eq!(logs[0].topics[0], H256::from([
+		0x85, 0x7a, 0xb1, 0xc7, 0x5d, 0x12, 0x6c, 0x08, 0x33, 0x00, 0x14, 0xc5, 0xb9, 0x84, 0x63, 0x6a,
+		0x81, 0x64, 0xd9, 0x9d, 0x11, 0xef, 0xb0, 0x25, 0x88, 0x82, 0x79, 0x16, 0x82, 0xd1, 0x70, 0xab,
+	]));
+	// keccak256("abc")
+	assert_eq!(logs[0].topics[1], H256::from([
+		0x4e, 0x03, 0x65, 0x7a, 0xea, 0x45, 0xa9, 0x4f, 0xc7, 0xd4, 0x7b, 0xa8, 0x26, 0xc8, 0xd6, 0x67,
+		0xc0, 0xd1, 0xe6, 0xe3, 0x3a, 0x64, 0xa0, 0x36, 0xec, 0x44, 0xf5, 0x8f, 0xa1, 0x2d, 0x6c, 0x45,
+	]));
+	// keccak256(hex"0102")
+	assert_eq!(logs[0].topics[2], H256::from([
+		0x22, 0xae, 0x6d, 0xa6, 0xb4, 0x82, 0xf9, 0xb1, 0xb1, 0x9b, 0x0b, 0x89, 0x7c, 0x3f, 0xd4, 0x38,
+		0x84, 0x18, 0x0a, 0x1c, 0x5e, 0xe3, 0x61, 0xe1, 0x10, 0x7a, 0x1b, 0xc6, 0x35, 0x64, 0x9d, 0xda,
+	]));
+}
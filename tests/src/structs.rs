@@ -0,0 +1,185 @@
+#![allow(dead_code)]
+
+use pwasm_abi::eth::{AbiType, AbiEncode, AsLog, EndpointInterface, PackedSink};
+use pwasm_abi::types::{U256, H256, Address};
+use pwasm_abi_derive::{eth_abi, AbiType};
+
+#[derive(AbiType, Debug, PartialEq)]
+pub struct Transfer {
+	to: Address,
+	amount: U256,
+}
+
+#[derive(AbiType, Debug, PartialEq)]
+pub struct Named {
+	name: String,
+	value: U256,
+}
+
+#[eth_abi(StructsEndpoint, StructsClient)]
+pub trait StructsContract {
+	fn transfer(&mut self, #[abi_type = "(address,uint256)"] transfer: Transfer);
+	fn transfers(&mut self, #[abi_type = "(address,uint256)[]"] transfers: Vec<Transfer>);
+	#[abi_returns = "(string,uint256)"]
+	fn named_of(&mut self, value: U256) -> Named;
+	#[abi_returns = "(address,uint256)"]
+	fn last_transfer(&mut self) -> Transfer;
+}
+
+// transfer((address,uint256))
+pub const PAYLOAD_TRANSFER: &[u8] = &[
+	0x2b, 0xd1, 0x4b, 0xb9,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xe8,
+];
+
+// transfers((address,uint256)[])
+pub const PAYLOAD_TRANSFERS: &[u8] = &[
+	0xfb, 0xff, 0x29, 0xfc,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x11, 0x11, 0x11, 0x11,
+	0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x22, 0x22, 0x22, 0x22,
+	0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22, 0x22,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+];
+
+// named_of(uint256)
+pub const PAYLOAD_NAMED_OF: &[u8] = &[
+	0x0d, 0x4b, 0x61, 0xdb,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+];
+
+// last_transfer()
+pub const PAYLOAD_LAST_TRANSFER: &[u8] = &[0x2e, 0x82, 0xaa, 0x31];
+
+#[derive(Default)]
+pub struct Instance {
+	transfers: Vec<Transfer>,
+}
+
+impl StructsContract for Instance {
+	fn transfer(&mut self, transfer: Transfer) {
+		self.transfers.push(transfer);
+	}
+
+	fn transfers(&mut self, transfers: Vec<Transfer>) {
+		self.transfers.extend(transfers);
+	}
+
+	fn named_of(&mut self, value: U256) -> Named {
+		Named { name: "abc".to_owned(), value: value }
+	}
+
+	fn last_transfer(&mut self) -> Transfer {
+		self.transfers.pop().expect("no transfers")
+	}
+}
+
+#[test]
+fn transfer() {
+	let mut endpoint = StructsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_TRANSFER);
+
+	assert_eq!(endpoint.inner.transfers, vec![
+		Transfer { to: Address::from([0x11u8; 20]), amount: U256::from(1000) },
+	]);
+}
+
+#[test]
+fn transfers() {
+	let mut endpoint = StructsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_TRANSFERS);
+
+	assert_eq!(endpoint.inner.transfers, vec![
+		Transfer { to: Address::from([0x11u8; 20]), amount: U256::from(1) },
+		Transfer { to: Address::from([0x22u8; 20]), amount: U256::from(2) },
+	]);
+}
+
+#[test]
+fn named_of() {
+	let mut endpoint = StructsEndpoint::new(Instance::default());
+	let result = endpoint.dispatch(PAYLOAD_NAMED_OF);
+
+	assert_eq!(&result[..], &[
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+		0x61, 0x62, 0x63, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	][..]);
+}
+
+#[test]
+fn last_transfer() {
+	let mut endpoint = StructsEndpoint::new(Instance::default());
+	endpoint.dispatch(PAYLOAD_TRANSFER);
+	let result = endpoint.dispatch(PAYLOAD_LAST_TRANSFER);
+
+	// static tuple takes two words in the head
+	assert_eq!(&result[..], &PAYLOAD_TRANSFER[4..]);
+}
+
+#[test]
+fn canonical_name() {
+	assert_eq!(Transfer::canonical_name(), "(address,uint256)");
+	assert_eq!(<Vec<Named>>::canonical_name(), "(string,uint256)[]");
+}
+
+#[test]
+fn encoded_size() {
+	assert_eq!(Transfer::HEAD_SIZE, 64);
+	assert_eq!(Named::HEAD_SIZE, 32);
+
+	let named = Named { name: "abc".to_owned(), value: U256::from(7) };
+	// offset, then the heads of the fields and the name
+	assert_eq!(named.encoded_size(), 4 * 32);
+}
+
+#[test]
+fn packed() {
+	let mut sink = PackedSink::new();
+	sink.push(Named { name: "abc".to_owned(), value: U256::from(7) });
+
+	// fields are concatenated, as for abi.encodePacked(name, value)
+	assert_eq!(&sink.finalize()[..], &[
+		0x61, 0x62, 0x63,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07,
+	][..]);
+}
+
+#[test]
+fn packed_array() {
+	let mut sink = PackedSink::new();
+	sink.push(vec![Transfer { to: Address::from([0x11u8; 20]), amount: U256::from(1000) }]);
+
+	// the items of the arrays are padded, as for abi.encodePacked(transfers)
+	assert_eq!(&sink.finalize()[..], &PAYLOAD_TRANSFER[4..]);
+}
+
+#[test]
+fn as_log() {
+	let named = Named { name: "a".to_owned(), value: U256::from(1) };
+
+	// keccak256 of the fields encoded in place, the name is padded without length
+	assert_eq!(named.as_log(), H256::from([
+		0x8f, 0x1d, 0xec, 0xdd, 0x0a, 0xa2, 0x2b, 0x72, 0x1a, 0x44, 0x2d, 0xa0, 0x68, 0x53, 0x84, 0xae,
+		0xd2, 0xd6, 0x88, 0x4c, 0x2b, 0x83, 0x53, 0x60, 0xd7, 0xf5, 0x7a, 0xc4, 0x6d, 0x88, 0x1a, 0xe7,
+	]));
+}